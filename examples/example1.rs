@@ -8,6 +8,7 @@ struct Projectile {
 
 struct Environment {
     gravity: Tuple,
+    #[allow(dead_code)]
     wind: Tuple,
 }
 
@@ -23,11 +23,11 @@ fn main() {
     let mut point = Matrix4::translate(0., 0., radius) * origin;
 
 
-    canvas.write_pixel((origin_x + point.x) as usize, (origin_y - point.z) as usize, color.clone());
+    canvas.write_pixel((origin_x + point.x) as usize, (origin_y - point.z) as usize, color);
 
     for _ in 0..12 {
         point = Matrix4::rotation_y(PI / 6.) * point;
-        canvas.write_pixel((origin_x + point.x) as usize, (origin_y - point.z) as usize, color.clone());
+        canvas.write_pixel((origin_x + point.x) as usize, (origin_y - point.z) as usize, color);
     }
 
     
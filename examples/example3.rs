@@ -1,4 +1,5 @@
 use raytracer::ray::Ray;
+use raytracer::shape::Shape;
 use raytracer::sphere::Sphere;
 use raytracer::{point, tuple::Tuple, Canvas, Color};
 
@@ -40,7 +41,7 @@ fn main() {
 
             let xs = sphere.intersect(ray);
 
-            if xs.hit() != None {
+            if xs.hit().is_some() {
                 canvas.write_pixel(x, y, yellow);
             }
         }
@@ -1,6 +1,7 @@
-use raytracer::material::PointLight;
+use raytracer::light::PointLight;
 use raytracer::ray::Ray;
 use raytracer::sphere::Sphere;
+use raytracer::world::World;
 use raytracer::{point, tuple::Tuple, Canvas, Color};
 
 fn main() {
@@ -27,6 +28,10 @@ fn main() {
         green: 1.,
     };
 
+    let mut world = World::new();
+    world.add_object(sphere);
+    world.add_light(PointLight::new(light_position, light_color));
+
     for y in 0..canvas_size {
         for x in 0..canvas_size {
             let half = wall_size / 2.0;
@@ -39,21 +44,7 @@ fn main() {
                 direction: (wall_point - ray_origin).normalize(),
             };
 
-            let xs = sphere.intersect(ray);
-
-            if xs.hit() != None {
-                let light = PointLight::new(light_position, light_color);
-
-                let hit_object = xs.hit().unwrap();
-                let point = ray.position(hit_object.t);
-                let normal = hit_object.object.normal_at(point);
-                let eye = -ray.direction;
-                let color = hit_object
-                    .object
-                    .material
-                    .lightning(light, point, eye, normal);
-                canvas.write_pixel(x, y, color);
-            }
+            canvas.write_pixel(x, y, world.color_at(ray));
         }
     }
     canvas.save();
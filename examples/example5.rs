@@ -0,0 +1,79 @@
+use std::f64::consts::PI;
+use std::sync::Arc;
+
+use raytracer::camera::Camera;
+use raytracer::light::PointLight;
+use raytracer::matrix::Matrix4;
+use raytracer::pattern::Checkers;
+use raytracer::plane::Plane;
+use raytracer::shape::Shape;
+use raytracer::sphere::Sphere;
+use raytracer::world::World;
+use raytracer::{point, tuple::Tuple, vector, Color};
+
+fn main() {
+    let mut floor = Plane::default();
+    floor.material.color = Color::new(1., 0.9, 0.9);
+    floor.material.specular = 0.;
+
+    let walls = floor.material.clone();
+    floor.material.pattern = Some(Arc::new(Checkers::new(
+        Color::new(1., 0.9, 0.9),
+        Color::new(0.6, 0.5, 0.5),
+    )));
+
+    let mut left_wall = Plane::default();
+    left_wall.set_transform(
+        Matrix4::translate(0., 0., 5.)
+            * Matrix4::rotation_y(-PI / 4.)
+            * Matrix4::rotation_x(PI / 2.),
+    );
+    left_wall.material = walls.clone();
+
+    let mut right_wall = Plane::default();
+    right_wall.set_transform(
+        Matrix4::translate(0., 0., 5.)
+            * Matrix4::rotation_y(PI / 4.)
+            * Matrix4::rotation_x(PI / 2.),
+    );
+    right_wall.material = walls;
+
+    let mut middle = Sphere::default();
+    middle.set_transform(Matrix4::translate(-0.5, 1., 0.5));
+    middle.material.color = Color::new(0.1, 1., 0.5);
+    middle.material.diffuse = 0.7;
+    middle.material.specular = 0.3;
+
+    let mut right = Sphere::default();
+    right.set_transform(Matrix4::translate(1.5, 0.5, -0.5) * Matrix4::scaling(0.5, 0.5, 0.5));
+    right.material.color = Color::new(0.5, 1., 0.1);
+    right.material.diffuse = 0.7;
+    right.material.specular = 0.3;
+
+    let mut left = Sphere::default();
+    left.set_transform(Matrix4::translate(-1.5, 0.33, -0.75) * Matrix4::scaling(0.33, 0.33, 0.33));
+    left.material.color = Color::new(1., 0.8, 0.1);
+    left.material.diffuse = 0.7;
+    left.material.specular = 0.3;
+
+    let mut world = World::new();
+    for wall in [floor, left_wall, right_wall] {
+        world.add_object(wall);
+    }
+    for object in [middle, right, left] {
+        world.add_object(object);
+    }
+    world.add_light(PointLight::new(
+        point!(-10., 10., -10.),
+        Color::new(1., 1., 1.),
+    ));
+
+    let mut camera = Camera::new(400, 200, PI / 3.);
+    camera.set_transform(Matrix4::view_transform(
+        point!(0., 1.5, -5.),
+        point!(0., 1., 0.),
+        vector!(0., 1., 0.),
+    ));
+
+    camera.render(&world).save();
+}
@@ -0,0 +1,249 @@
+use crate::{matrix::Matrix4, point, ray::Ray, tuple::Tuple};
+
+/// An axis-aligned box enclosing a shape in its own object space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BoundingBox {
+    pub min: Tuple,
+    pub max: Tuple,
+}
+
+impl BoundingBox {
+    pub fn new(min: Tuple, max: Tuple) -> Self {
+        Self { min, max }
+    }
+
+    pub fn add_point(&mut self, p: Tuple) {
+        self.min.x = self.min.x.min(p.x);
+        self.min.y = self.min.y.min(p.y);
+        self.min.z = self.min.z.min(p.z);
+        self.max.x = self.max.x.max(p.x);
+        self.max.y = self.max.y.max(p.y);
+        self.max.z = self.max.z.max(p.z);
+    }
+
+    pub fn merge(&mut self, other: BoundingBox) {
+        self.add_point(other.min);
+        self.add_point(other.max);
+    }
+
+    /// False for empty boxes and for boxes reaching infinity, like those of planes.
+    pub fn is_finite(&self) -> bool {
+        [self.min, self.max]
+            .iter()
+            .all(|p| p.x.is_finite() && p.y.is_finite() && p.z.is_finite())
+    }
+
+    pub fn centroid(&self) -> Tuple {
+        point!(
+            (self.min.x + self.max.x) / 2.,
+            (self.min.y + self.max.y) / 2.,
+            (self.min.z + self.max.z) / 2.
+        )
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        let d = self.max - self.min;
+        2. * (d.x * d.y + d.y * d.z + d.z * d.x)
+    }
+
+    pub fn contains_point(&self, p: Tuple) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    /// Whether `ray` passes through the box ahead of its origin. Unlike `check_axis` this
+    /// divides exactly, so nearly parallel rays aren't culled by mistake.
+    pub fn intersects(&self, ray: Ray) -> bool {
+        let mut tmin = f64::NEG_INFINITY;
+        let mut tmax = f64::INFINITY;
+
+        for (origin, direction, min, max) in [
+            (ray.origin.x, ray.direction.x, self.min.x, self.max.x),
+            (ray.origin.y, ray.direction.y, self.min.y, self.max.y),
+            (ray.origin.z, ray.direction.z, self.min.z, self.max.z),
+        ] {
+            if direction == 0. {
+                if origin < min || origin > max {
+                    return false;
+                }
+                continue;
+            }
+            let t1 = (min - origin) / direction;
+            let t2 = (max - origin) / direction;
+            tmin = tmin.max(t1.min(t2));
+            tmax = tmax.min(t1.max(t2));
+        }
+
+        tmin <= tmax && tmax >= 0.
+    }
+
+    /// The axis-aligned box enclosing this box's eight corners after `matrix` is applied.
+    pub fn transform(&self, matrix: Matrix4) -> BoundingBox {
+        let (min, max) = (self.min, self.max);
+        let corners = [
+            point!(min.x, min.y, min.z),
+            point!(min.x, min.y, max.z),
+            point!(min.x, max.y, min.z),
+            point!(min.x, max.y, max.z),
+            point!(max.x, min.y, min.z),
+            point!(max.x, min.y, max.z),
+            point!(max.x, max.y, min.z),
+            point!(max.x, max.y, max.z),
+        ];
+
+        let mut b = BoundingBox::default();
+        for corner in corners {
+            b.add_point(transform_point(matrix, corner));
+        }
+        b
+    }
+}
+
+/// `matrix * p`, skipping zero entries so infinite coordinates don't turn into NaN.
+fn transform_point(matrix: Matrix4, p: Tuple) -> Tuple {
+    let coords = [p.x, p.y, p.z, p.w];
+    let row = |r: [f64; 4]| -> f64 {
+        r.iter()
+            .zip(coords)
+            .filter(|(m, _)| **m != 0.)
+            .map(|(m, c)| m * c)
+            .sum()
+    };
+
+    Tuple {
+        x: row(matrix.0[0]),
+        y: row(matrix.0[1]),
+        z: row(matrix.0[2]),
+        w: 1.,
+    }
+}
+
+impl Default for BoundingBox {
+    /// An empty box, ready to grow around whatever is added to it.
+    fn default() -> Self {
+        Self {
+            min: point!(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            max: point!(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{PI, SQRT_2};
+
+    use crate::{equal, matrix::Matrix4, point, ray::Ray, test_point, tuple::Tuple, vector};
+
+    use super::BoundingBox;
+
+    #[test]
+    fn creating_an_empty_bounding_box() {
+        let b = BoundingBox::default();
+        assert_eq!(b.min, point!(f64::INFINITY, f64::INFINITY, f64::INFINITY));
+        assert_eq!(
+            b.max,
+            point!(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY)
+        );
+    }
+
+    #[test]
+    fn creating_a_bounding_box_with_volume() {
+        let b = BoundingBox::new(point!(-1., -2., -3.), point!(3., 2., 1.));
+        assert_eq!(b.min, point!(-1., -2., -3.));
+        assert_eq!(b.max, point!(3., 2., 1.));
+    }
+
+    #[test]
+    fn adding_points_to_an_empty_bounding_box() {
+        let mut b = BoundingBox::default();
+        b.add_point(point!(-5., 2., 0.));
+        b.add_point(point!(7., 0., -3.));
+        assert_eq!(b.min, point!(-5., 0., -3.));
+        assert_eq!(b.max, point!(7., 2., 0.));
+    }
+
+    #[test]
+    fn adding_one_bounding_box_to_another() {
+        let mut b1 = BoundingBox::new(point!(-5., -2., 0.), point!(7., 4., 4.));
+        let b2 = BoundingBox::new(point!(8., -7., -2.), point!(14., 2., 8.));
+        b1.merge(b2);
+        assert_eq!(b1.min, point!(-5., -7., -2.));
+        assert_eq!(b1.max, point!(14., 4., 8.));
+    }
+
+    #[test]
+    fn transforming_a_bounding_box() {
+        let b = BoundingBox::new(point!(-1., -1., -1.), point!(1., 1., 1.));
+        let matrix = Matrix4::rotation_x(PI / 4.) * Matrix4::rotation_y(PI / 4.);
+        let b2 = b.transform(matrix);
+        test_point!(b2.min, point!(-SQRT_2, -1.70710, -1.70710));
+        test_point!(b2.max, point!(SQRT_2, 1.70710, 1.70710));
+    }
+
+    #[test]
+    fn transforming_an_infinite_bounding_box() {
+        let b = BoundingBox::new(
+            point!(f64::NEG_INFINITY, 0., f64::NEG_INFINITY),
+            point!(f64::INFINITY, 0., f64::INFINITY),
+        );
+        let b2 = b.transform(Matrix4::translate(0., -1., 0.));
+        assert_eq!(b2.min, point!(f64::NEG_INFINITY, -1., f64::NEG_INFINITY));
+        assert_eq!(b2.max, point!(f64::INFINITY, -1., f64::INFINITY));
+    }
+
+    #[test]
+    fn checking_whether_a_box_contains_a_point() {
+        let b = BoundingBox::new(point!(5., -2., 0.), point!(11., 4., 7.));
+        let cases = [
+            (point!(5., -2., 0.), true),
+            (point!(11., 4., 7.), true),
+            (point!(8., 1., 3.), true),
+            (point!(3., 0., 3.), false),
+            (point!(8., -4., 3.), false),
+            (point!(8., 1., -1.), false),
+            (point!(13., 1., 3.), false),
+            (point!(8., 5., 3.), false),
+            (point!(8., 1., 8.), false),
+        ];
+        for (p, result) in cases {
+            assert_eq!(b.contains_point(p), result);
+        }
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_non_cubic_bounding_box() {
+        let b = BoundingBox::new(point!(5., -2., 0.), point!(11., 4., 7.));
+        let cases = [
+            (point!(15., 1., 2.), vector!(-1., 0., 0.), true),
+            (point!(-5., -1., 4.), vector!(1., 0., 0.), true),
+            (point!(7., 6., 5.), vector!(0., -1., 0.), true),
+            (point!(9., -5., 6.), vector!(0., 1., 0.), true),
+            (point!(8., 2., 12.), vector!(0., 0., -1.), true),
+            (point!(6., 0., -5.), vector!(0., 0., 1.), true),
+            (point!(8., 1., 3.5), vector!(0., 0., 1.), true),
+            (point!(9., -1., -8.), vector!(2., 4., 6.), false),
+            (point!(8., 3., -4.), vector!(6., 2., 4.), false),
+            (point!(9., -1., -2.), vector!(4., 6., 2.), false),
+            (point!(4., 0., 9.), vector!(6., 2., 4.), false),
+            (point!(8., 6., -1.), vector!(2., 4., 6.), false),
+            (point!(12., 5., 4.), vector!(4., 6., 2.), false),
+        ];
+        for (origin, direction, result) in cases {
+            let r = Ray {
+                origin,
+                direction: direction.normalize(),
+            };
+            assert_eq!(b.intersects(r), result);
+        }
+    }
+
+    #[test]
+    fn surface_area_and_centroid() {
+        let b = BoundingBox::new(point!(-1., -2., -3.), point!(1., 2., 3.));
+        assert_eq!(b.surface_area(), 2. * (2. * 4. + 4. * 6. + 6. * 2.));
+        assert_eq!(b.centroid(), point!(0., 0., 0.));
+        assert!(b.is_finite());
+        assert!(!BoundingBox::default().is_finite());
+    }
+}
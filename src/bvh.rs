@@ -0,0 +1,427 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::{
+    bounds::BoundingBox,
+    intersection::{Intersection, Intersections},
+    ray::Ray,
+    shape::Shape,
+    tuple::Tuple,
+};
+
+/// Nodes holding this many shapes or fewer are never split.
+const LEAF_SIZE: usize = 2;
+
+/// Number of buckets candidate splits are evaluated at along each axis.
+const BINS: usize = 12;
+
+/// Cost of visiting a node relative to intersecting one shape, for the surface area heuristic.
+const TRAVERSAL_COST: f64 = 0.5;
+
+/// Counters accumulated while tracing rays through a [`Bvh`].
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct BvhStats {
+    pub rays: usize,
+    pub nodes_visited: usize,
+    pub shapes_tested: usize,
+}
+
+#[derive(Debug)]
+enum Node {
+    Leaf {
+        bounds: BoundingBox,
+        shapes: Vec<usize>,
+    },
+    Interior {
+        bounds: BoundingBox,
+        left: usize,
+        right: usize,
+    },
+}
+
+impl Node {
+    fn bounds(&self) -> BoundingBox {
+        match self {
+            Node::Leaf { bounds, .. } | Node::Interior { bounds, .. } => *bounds,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Item {
+    index: usize,
+    bounds: BoundingBox,
+    centroid: Tuple,
+}
+
+fn axis(t: Tuple, axis: usize) -> f64 {
+    match axis {
+        0 => t.x,
+        1 => t.y,
+        _ => t.z,
+    }
+}
+
+/// A bounding volume hierarchy over a list of shapes, split with the surface area heuristic.
+/// It stores indices, so it must be queried with the same list it was built from.
+#[derive(Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    root: Option<usize>,
+    /// Shapes without finite bounds, like planes, which every ray has to test.
+    unbounded: Vec<usize>,
+    /// Whether rays update the counters below. Off by default: they're shared by every
+    /// render thread, so counting slows the renderer down.
+    record_stats: bool,
+    rays: AtomicUsize,
+    nodes_visited: AtomicUsize,
+    shapes_tested: AtomicUsize,
+}
+
+impl Bvh {
+    pub fn build(shapes: &[Box<dyn Shape>]) -> Self {
+        let mut bvh = Bvh::default();
+        let mut items = vec![];
+
+        for (index, shape) in shapes.iter().enumerate() {
+            let bounds = shape.parent_space_bounds();
+            if bounds.is_finite() {
+                items.push(Item {
+                    index,
+                    bounds,
+                    centroid: bounds.centroid(),
+                });
+            } else {
+                bvh.unbounded.push(index);
+            }
+        }
+
+        if !items.is_empty() {
+            bvh.root = Some(bvh.build_node(items));
+        }
+
+        bvh
+    }
+
+    fn push(&mut self, node: Node) -> usize {
+        self.nodes.push(node);
+        self.nodes.len() - 1
+    }
+
+    fn leaf(&mut self, bounds: BoundingBox, items: Vec<Item>) -> usize {
+        let shapes = items.iter().map(|item| item.index).collect();
+        self.push(Node::Leaf { bounds, shapes })
+    }
+
+    fn build_node(&mut self, mut items: Vec<Item>) -> usize {
+        let mut bounds = BoundingBox::default();
+        let mut centroids = BoundingBox::default();
+        for item in &items {
+            bounds.merge(item.bounds);
+            centroids.add_point(item.centroid);
+        }
+
+        if items.len() <= LEAF_SIZE {
+            return self.leaf(bounds, items);
+        }
+
+        let Some((split_axis, split_bin, cost)) = best_split(&items, bounds, centroids) else {
+            // every centroid sits on the same spot, so no plane separates them
+            return self.leaf(bounds, items);
+        };
+
+        if cost >= items.len() as f64 {
+            return self.leaf(bounds, items);
+        }
+
+        let lo = axis(centroids.min, split_axis);
+        let extent = axis(centroids.max, split_axis) - lo;
+        let right_items: Vec<Item>;
+        (items, right_items) = items
+            .into_iter()
+            .partition(|item| bin_of(axis(item.centroid, split_axis), lo, extent) < split_bin);
+
+        let left = self.build_node(items);
+        let right = self.build_node(right_items);
+        self.push(Node::Interior {
+            bounds,
+            left,
+            right,
+        })
+    }
+
+    /// Every intersection of `ray` with `shapes`, sorted by `t`.
+    pub fn intersect<'a>(&self, shapes: &'a [Box<dyn Shape>], ray: Ray) -> Intersections<'a> {
+        let mut xs: Vec<Intersection<'a>> = vec![];
+        let mut nodes_visited = 0;
+        let mut shapes_tested = self.unbounded.len();
+
+        for &i in &self.unbounded {
+            xs.extend(shapes[i].intersect(ray).0);
+        }
+
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            nodes_visited += 1;
+
+            if !node.bounds().intersects(ray) {
+                continue;
+            }
+
+            match node {
+                Node::Leaf { shapes: leaf, .. } => {
+                    shapes_tested += leaf.len();
+                    for &i in leaf {
+                        xs.extend(shapes[i].intersect(ray).0);
+                    }
+                }
+                Node::Interior { left, right, .. } => {
+                    stack.push(*right);
+                    stack.push(*left);
+                }
+            }
+        }
+
+        if self.record_stats {
+            self.rays.fetch_add(1, Ordering::Relaxed);
+            self.nodes_visited
+                .fetch_add(nodes_visited, Ordering::Relaxed);
+            self.shapes_tested
+                .fetch_add(shapes_tested, Ordering::Relaxed);
+        }
+
+        let mut xs = Intersections(xs);
+        xs.sort();
+        xs
+    }
+
+    /// Starts or stops counting traversal work for [`Bvh::stats`].
+    pub fn record_stats(&mut self, record: bool) {
+        self.record_stats = record;
+    }
+
+    /// Counters for the rays traced while stats were being recorded.
+    pub fn stats(&self) -> BvhStats {
+        BvhStats {
+            rays: self.rays.load(Ordering::Relaxed),
+            nodes_visited: self.nodes_visited.load(Ordering::Relaxed),
+            shapes_tested: self.shapes_tested.load(Ordering::Relaxed),
+        }
+    }
+
+    pub fn reset_stats(&self) {
+        self.rays.store(0, Ordering::Relaxed);
+        self.nodes_visited.store(0, Ordering::Relaxed);
+        self.shapes_tested.store(0, Ordering::Relaxed);
+    }
+
+    pub fn depth(&self) -> usize {
+        fn depth_of(nodes: &[Node], index: usize) -> usize {
+            match &nodes[index] {
+                Node::Leaf { .. } => 1,
+                Node::Interior { left, right, .. } => {
+                    1 + depth_of(nodes, *left).max(depth_of(nodes, *right))
+                }
+            }
+        }
+
+        self.root.map_or(0, |root| depth_of(&self.nodes, root))
+    }
+}
+
+fn bin_of(value: f64, lo: f64, extent: f64) -> usize {
+    (((value - lo) / extent * BINS as f64) as usize).min(BINS - 1)
+}
+
+/// The axis and bucket boundary with the lowest estimated cost, together with that cost
+/// in units of shape intersections.
+fn best_split(
+    items: &[Item],
+    bounds: BoundingBox,
+    centroids: BoundingBox,
+) -> Option<(usize, usize, f64)> {
+    let parent_area = bounds.surface_area();
+    let mut best: Option<(usize, usize, f64)> = None;
+
+    for split_axis in 0..3 {
+        let lo = axis(centroids.min, split_axis);
+        let extent = axis(centroids.max, split_axis) - lo;
+        if extent <= 0. {
+            continue;
+        }
+
+        let mut bins = [(BoundingBox::default(), 0_usize); BINS];
+        for item in items {
+            let bin = &mut bins[bin_of(axis(item.centroid, split_axis), lo, extent)];
+            bin.0.merge(item.bounds);
+            bin.1 += 1;
+        }
+
+        for split in 1..BINS {
+            let (mut left, mut right) = (BoundingBox::default(), BoundingBox::default());
+            let (mut left_count, mut right_count) = (0, 0);
+            for (b, count) in &bins[..split] {
+                if *count > 0 {
+                    left.merge(*b);
+                    left_count += count;
+                }
+            }
+            for (b, count) in &bins[split..] {
+                if *count > 0 {
+                    right.merge(*b);
+                    right_count += count;
+                }
+            }
+            if left_count == 0 || right_count == 0 {
+                continue;
+            }
+
+            // degenerate (flat or point-like) parents fall back to balancing the counts
+            let cost = if parent_area > 0. {
+                TRAVERSAL_COST
+                    + (left.surface_area() * left_count as f64
+                        + right.surface_area() * right_count as f64)
+                        / parent_area
+            } else {
+                TRAVERSAL_COST + left_count.max(right_count) as f64
+            };
+
+            if best.map_or(true, |(_, _, best_cost)| cost < best_cost) {
+                best = Some((split_axis, split, cost));
+            }
+        }
+    }
+
+    best
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        matrix::Matrix4, plane::Plane, point, ray::Ray, shape::Shape, sphere::Sphere, tuple::Tuple,
+        vector,
+    };
+
+    use super::{Bvh, BvhStats};
+
+    /// A `n`×`n`×`n` lattice of small spheres spaced three units apart.
+    fn sphere_grid(n: usize) -> Vec<Box<dyn Shape>> {
+        let mut shapes: Vec<Box<dyn Shape>> = vec![];
+        for x in 0..n {
+            for y in 0..n {
+                for z in 0..n {
+                    let mut s = Sphere::default();
+                    s.set_transform(Matrix4::translate(
+                        x as f64 * 3.,
+                        y as f64 * 3.,
+                        z as f64 * 3.,
+                    ));
+                    shapes.push(Box::new(s));
+                }
+            }
+        }
+        shapes
+    }
+
+    fn brute_force_ts(shapes: &[Box<dyn Shape>], r: Ray) -> Vec<f64> {
+        let mut ts: Vec<f64> = shapes
+            .iter()
+            .flat_map(|s| s.intersect(r).0)
+            .map(|i| i.t)
+            .collect();
+        ts.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        ts
+    }
+
+    #[test]
+    fn empty_bvh_has_no_intersections() {
+        let shapes: Vec<Box<dyn Shape>> = vec![];
+        let bvh = Bvh::build(&shapes);
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        assert!(bvh.intersect(&shapes, r).0.is_empty());
+        assert_eq!(bvh.depth(), 0);
+    }
+
+    #[test]
+    fn bvh_finds_the_same_intersections_as_testing_every_shape() {
+        let shapes = sphere_grid(6);
+        let bvh = Bvh::build(&shapes);
+
+        for r in [
+            Ray {
+                origin: point!(-5., 0., 0.),
+                direction: vector!(1., 0., 0.),
+            },
+            Ray {
+                origin: point!(-5., -5., -5.),
+                direction: vector!(1., 1., 1.).normalize(),
+            },
+            Ray {
+                origin: point!(7.5, 50., 6.),
+                direction: vector!(0., -1., 0.),
+            },
+        ] {
+            let ts: Vec<f64> = bvh.intersect(&shapes, r).0.iter().map(|i| i.t).collect();
+            assert_eq!(ts, brute_force_ts(&shapes, r));
+        }
+    }
+
+    #[test]
+    fn bvh_tests_a_fraction_of_the_shapes() {
+        let shapes = sphere_grid(10);
+        let mut bvh = Bvh::build(&shapes);
+        bvh.record_stats(true);
+        assert!(bvh.depth() < 20);
+
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = bvh.intersect(&shapes, r);
+        assert_eq!(xs.0.len(), 20);
+
+        let stats = bvh.stats();
+        assert_eq!(stats.rays, 1);
+        assert!(stats.shapes_tested < 40);
+        assert!(stats.nodes_visited < 200);
+
+        bvh.reset_stats();
+        assert_eq!(bvh.stats().rays, 0);
+    }
+
+    #[test]
+    fn stats_are_only_counted_when_recording() {
+        let shapes = sphere_grid(2);
+        let mut bvh = Bvh::build(&shapes);
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        bvh.intersect(&shapes, r);
+        assert_eq!(bvh.stats(), BvhStats::default());
+
+        bvh.record_stats(true);
+        bvh.intersect(&shapes, r);
+        assert_eq!(bvh.stats().rays, 1);
+    }
+
+    #[test]
+    fn unbounded_shapes_are_always_tested() {
+        let mut shapes = sphere_grid(2);
+        let mut floor = Plane::default();
+        floor.set_transform(Matrix4::translate(0., -10., 0.));
+        shapes.push(Box::new(floor));
+        let bvh = Bvh::build(&shapes);
+
+        let r = Ray {
+            origin: point!(100., 0., 100.),
+            direction: vector!(0., -1., 0.),
+        };
+        let xs = bvh.intersect(&shapes, r);
+        assert_eq!(xs.0.len(), 1);
+        assert_eq!(xs.0[0].t, 10.);
+    }
+}
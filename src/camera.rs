@@ -0,0 +1,491 @@
+use std::{
+    f64::consts::PI,
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+use crate::{
+    matrix::Matrix4, point, ray::Ray, rng::Rng, tuple::Tuple, world::World, Canvas, Color, Tile,
+    BLACK,
+};
+
+/// Edge length in pixels of the square tiles handed out to render threads.
+pub const TILE_SIZE: usize = 16;
+
+/// Maps the canvas onto a view of the world one unit in front of the eye.
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+    hsize: usize,
+    vsize: usize,
+    field_of_view: f64,
+    /// Radius of the lens. 0 is a pinhole, with everything in focus; anything larger
+    /// blurs what's nearer or farther than `focal_distance`.
+    aperture: f64,
+    /// How far in front of the camera things are sharp.
+    focal_distance: f64,
+    /// The number of straight blades forming the aperture, which shape out-of-focus
+    /// highlights. Fewer than 3 gives a round opening.
+    pub aperture_blades: usize,
+    /// Turns a bladed aperture's polygon, in radians.
+    pub aperture_rotation: f64,
+    /// Rays averaged per pixel when `aperture` is above 0.
+    pub lens_samples: usize,
+    transform: Matrix4,
+    /// Cached so rays aren't each paying for a matrix inversion.
+    inverse: Matrix4,
+    half_width: f64,
+    half_height: f64,
+    pixel_size: f64,
+}
+
+impl Camera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        let mut camera = Self {
+            hsize,
+            vsize,
+            field_of_view,
+            aperture: 0.,
+            focal_distance: 1.,
+            aperture_blades: 0,
+            aperture_rotation: 0.,
+            lens_samples: 16,
+            transform: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+            half_width: 0.,
+            half_height: 0.,
+            pixel_size: 0.,
+        };
+        camera.update_view();
+        camera
+    }
+
+    /// Sizes the view to fit the canvas and field of view.
+    fn update_view(&mut self) {
+        let half_view = (self.field_of_view / 2.).tan();
+        let aspect = self.hsize as f64 / self.vsize as f64;
+
+        (self.half_width, self.half_height) = if aspect >= 1. {
+            (half_view, half_view / aspect)
+        } else {
+            (half_view * aspect, half_view)
+        };
+        self.pixel_size = self.half_width * 2. / self.hsize as f64;
+    }
+
+    pub fn hsize(&self) -> usize {
+        self.hsize
+    }
+
+    pub fn vsize(&self) -> usize {
+        self.vsize
+    }
+
+    pub fn set_size(&mut self, hsize: usize, vsize: usize) {
+        self.hsize = hsize;
+        self.vsize = vsize;
+        self.update_view();
+    }
+
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+
+    pub fn set_field_of_view(&mut self, field_of_view: f64) {
+        self.field_of_view = field_of_view;
+        self.update_view();
+    }
+
+    pub fn transform(&self) -> Matrix4 {
+        self.transform
+    }
+
+    /// Panics if `t` can't be inverted.
+    pub fn set_transform(&mut self, t: Matrix4) {
+        self.transform = t;
+        self.inverse = t.inverse().expect("camera transform must be invertible");
+    }
+
+    pub fn aperture(&self) -> f64 {
+        self.aperture
+    }
+
+    pub fn focal_distance(&self) -> f64 {
+        self.focal_distance
+    }
+
+    /// Opens the lens to `aperture` and focuses it `focal_distance` away. Panics if the
+    /// lens is open but `focal_distance` isn't positive, since nothing could be in focus.
+    pub fn set_lens(&mut self, aperture: f64, focal_distance: f64) {
+        assert!(
+            aperture <= 0. || focal_distance > 0.,
+            "focal distance must be positive for a camera with an aperture"
+        );
+        self.aperture = aperture;
+        self.focal_distance = focal_distance;
+    }
+
+    pub fn pixel_size(&self) -> f64 {
+        self.pixel_size
+    }
+
+    pub fn ray_for_pixel(&self, x: usize, y: usize) -> Ray {
+        self.ray_through_lens(x, y, 0., 0.)
+    }
+
+    /// The ray for pixel `(x, y)` leaving the lens at `(lens_x, lens_y)`, relative to its
+    /// center. Every such ray for a pixel meets at the focal plane, so only things there
+    /// stay sharp.
+    pub fn ray_through_lens(&self, x: usize, y: usize, lens_x: f64, lens_y: f64) -> Ray {
+        // offset from the edge of the canvas to the pixel's center
+        let xoffset = (x as f64 + 0.5) * self.pixel_size;
+        let yoffset = (y as f64 + 0.5) * self.pixel_size;
+
+        // the camera looks toward -z, so +x is to the left
+        let world_x = self.half_width - xoffset;
+        let world_y = self.half_height - yoffset;
+
+        let target = if lens_x == 0. && lens_y == 0. {
+            // a pinhole ray: any point along it will do, whatever the focus
+            point!(world_x, world_y, -1.)
+        } else {
+            // where the pixel's pinhole ray crosses the focal plane
+            let d = self.focal_distance;
+            point!(world_x * d, world_y * d, -d)
+        };
+        let focus = self.inverse * target;
+        let origin = self.inverse * point!(lens_x, lens_y, 0.);
+
+        Ray {
+            origin,
+            direction: (focus - origin).normalize(),
+        }
+    }
+
+    /// A point on the aperture for `(u, v)` in `0..1`, spread evenly over its area: `u`
+    /// runs out from the center and `v` around it.
+    pub fn lens_point(&self, u: f64, v: f64) -> (f64, f64) {
+        if self.aperture_blades < 3 {
+            // uniform on the disk
+            let radius = self.aperture * u.sqrt();
+            let angle = 2. * PI * v;
+            return (radius * angle.cos(), radius * angle.sin());
+        }
+
+        // the polygon is a fan of triangles around the center: `v` picks one and how far
+        // along its outer edge, `u` how far out
+        let blades = self.aperture_blades as f64;
+        let scaled = v * blades;
+        let blade = scaled.floor().min(blades - 1.);
+        let (s, v) = (u.sqrt(), scaled - blade);
+        let corner = |i: f64| {
+            let angle = self.aperture_rotation + 2. * PI * i / blades;
+            (self.aperture * angle.cos(), self.aperture * angle.sin())
+        };
+        let (a, b) = (corner(blade), corner(blade + 1.));
+        (
+            s * ((1. - v) * a.0 + v * b.0),
+            s * ((1. - v) * a.1 + v * b.1),
+        )
+    }
+
+    /// The pixel's color: one ray for a pinhole, or the average over `lens_samples`
+    /// spots on the lens. The spots are seeded from the pixel, so renders repeat.
+    pub fn color_for_pixel(&self, world: &World, x: usize, y: usize) -> Color {
+        if self.aperture <= 0. || self.lens_samples == 0 {
+            return world.color_at(self.ray_for_pixel(x, y));
+        }
+
+        let mut rng = Rng::new((y * self.hsize + x) as u64);
+        let total = (0..self.lens_samples).fold(BLACK, |sum, i| {
+            // stratified around the aperture so the samples don't bunch up
+            let u = rng.next_f64();
+            let v = (i as f64 + rng.next_f64()) / self.lens_samples as f64;
+            let (lens_x, lens_y) = self.lens_point(u, v);
+            sum + world.color_at(self.ray_through_lens(x, y, lens_x, lens_y))
+        });
+        total / self.lens_samples as f64
+    }
+
+    /// Renders on every available core.
+    pub fn render(&self, world: &World) -> Canvas {
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        self.render_with_threads(world, threads)
+    }
+
+    /// Renders with `threads` workers taking tiles from a shared queue. Every pixel is
+    /// computed the same way whichever thread gets it, so the image doesn't depend on
+    /// the thread count.
+    pub fn render_with_threads(&self, world: &World, threads: usize) -> Canvas {
+        let mut image = Canvas::new(self.hsize, self.vsize);
+        let tiles = image.tiles(TILE_SIZE);
+        let next = AtomicUsize::new(0);
+
+        let rendered: Vec<(Tile, Vec<Color>)> = thread::scope(|scope| {
+            let workers: Vec<_> = (0..threads.max(1))
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut done = vec![];
+                        while let Some(&tile) = tiles.get(next.fetch_add(1, Ordering::Relaxed)) {
+                            done.push((tile, self.render_tile(world, tile)));
+                        }
+                        done
+                    })
+                })
+                .collect();
+
+            workers
+                .into_iter()
+                .flat_map(|worker| worker.join().unwrap())
+                .collect()
+        });
+
+        for (tile, colors) in rendered {
+            image.write_tile(tile, &colors);
+        }
+
+        image
+    }
+
+    fn render_tile(&self, world: &World, tile: Tile) -> Vec<Color> {
+        let mut colors = Vec::with_capacity(tile.width * tile.height);
+        for y in tile.y..tile.y + tile.height {
+            for x in tile.x..tile.x + tile.width {
+                colors.push(self.color_for_pixel(world, x, y));
+            }
+        }
+        colors
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        equal, matrix::Matrix4, point, ray::Ray, rng::Rng, test_color, test_point, tuple::Tuple,
+        vector, world::World, Color, EPSILON,
+    };
+
+    use super::Camera;
+
+    #[test]
+    fn constructing_a_camera() {
+        let c = Camera::new(160, 120, PI / 2.);
+        assert_eq!(c.hsize(), 160);
+        assert_eq!(c.vsize(), 120);
+        assert_eq!(c.field_of_view(), PI / 2.);
+        assert_eq!(c.transform(), Matrix4::identity());
+    }
+
+    #[test]
+    fn pixel_size_for_horizontal_canvas() {
+        let c = Camera::new(200, 125, PI / 2.);
+        assert!(equal(c.pixel_size(), 0.01));
+    }
+
+    #[test]
+    fn pixel_size_for_vertical_canvas() {
+        let c = Camera::new(125, 200, PI / 2.);
+        assert!(equal(c.pixel_size(), 0.01));
+    }
+
+    #[test]
+    fn resizing_the_view_updates_its_rays() {
+        let mut c = Camera::new(10, 10, PI / 3.);
+        c.set_size(125, 200);
+        c.set_field_of_view(PI / 2.);
+        assert!(equal(c.pixel_size(), 0.01));
+        assert_eq!(
+            c.ray_for_pixel(0, 0).direction,
+            Camera::new(125, 200, PI / 2.).ray_for_pixel(0, 0).direction
+        );
+    }
+
+    #[test]
+    fn ray_through_center_of_canvas() {
+        let c = Camera::new(201, 101, PI / 2.);
+        let r = c.ray_for_pixel(100, 50);
+        test_point!(r.origin, point!(0., 0., 0.));
+        test_point!(r.direction, vector!(0., 0., -1.));
+    }
+
+    #[test]
+    fn ray_through_corner_of_canvas() {
+        let c = Camera::new(201, 101, PI / 2.);
+        let r = c.ray_for_pixel(0, 0);
+        test_point!(r.origin, point!(0., 0., 0.));
+        test_point!(r.direction, vector!(0.66519, 0.33259, -0.66851));
+    }
+
+    #[test]
+    fn ray_when_camera_is_transformed() {
+        let mut c = Camera::new(201, 101, PI / 2.);
+        c.set_transform(Matrix4::rotation_y(PI / 4.) * Matrix4::translate(0., -2., 5.));
+        let r = c.ray_for_pixel(100, 50);
+        test_point!(r.origin, point!(0., 2., -5.));
+        test_point!(
+            r.direction,
+            vector!(2_f64.sqrt() / 2., 0., -2_f64.sqrt() / 2.)
+        );
+    }
+
+    #[test]
+    fn rendering_world_with_camera() {
+        let w = World::default();
+        let mut c = Camera::new(11, 11, PI / 2.);
+        let from = point!(0., 0., -5.);
+        let to = point!(0., 0., 0.);
+        let up = vector!(0., 1., 0.);
+        c.set_transform(Matrix4::view_transform(from, to, up));
+        let image = c.render(&w);
+        test_color!(image.pixel_at(5, 5), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn rendering_is_the_same_on_any_number_of_threads() {
+        let w = World::default();
+        let mut c = Camera::new(37, 21, PI / 3.);
+        c.set_transform(Matrix4::view_transform(
+            point!(0., 1.5, -5.),
+            point!(0., 0., 0.),
+            vector!(0., 1., 0.),
+        ));
+        let single = c.render_with_threads(&w, 1);
+        for threads in [2, 3, 8] {
+            let image = c.render_with_threads(&w, threads);
+            for y in 0..c.vsize() {
+                for x in 0..c.hsize() {
+                    assert_eq!(image.pixel_at(x, y), single.pixel_at(x, y));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn a_camera_starts_as_a_pinhole() {
+        let c = Camera::new(160, 120, PI / 2.);
+        assert_eq!(c.aperture(), 0.);
+        assert_eq!(c.focal_distance(), 1.);
+        assert_eq!(c.lens_point(0.7, 0.3), (0., 0.));
+    }
+
+    #[test]
+    fn lens_rays_for_a_pixel_meet_at_the_focal_plane() {
+        let mut c = Camera::new(201, 101, PI / 2.);
+        c.set_transform(Matrix4::rotation_y(PI / 4.) * Matrix4::translate(0., -2., 5.));
+        c.set_lens(0.5, 4.);
+
+        // the focal plane is `focal_distance` along the camera's view axis
+        let axis = c.ray_for_pixel(100, 50);
+        let focus = |r: Ray| {
+            let t = (4. - (r.origin - axis.origin).dot(axis.direction))
+                / r.direction.dot(axis.direction);
+            r.position(t)
+        };
+        let pinhole = focus(c.ray_for_pixel(20, 70));
+        for (lens_x, lens_y) in [(0.5, 0.), (-0.3, 0.2), (0.1, -0.4)] {
+            let r = c.ray_through_lens(20, 70, lens_x, lens_y);
+            test_point!(focus(r), pinhole);
+        }
+    }
+
+    #[test]
+    fn round_aperture_samples_stay_inside_the_lens() {
+        let mut c = Camera::new(10, 10, PI / 2.);
+        c.set_lens(0.25, 1.);
+        let mut rng = Rng::new(3);
+        for _ in 0..500 {
+            let (x, y) = c.lens_point(rng.next_f64(), rng.next_f64());
+            assert!((x * x + y * y).sqrt() <= 0.25 + EPSILON);
+        }
+    }
+
+    #[test]
+    fn bladed_aperture_samples_stay_inside_the_polygon() {
+        let mut c = Camera::new(10, 10, PI / 2.);
+        c.set_lens(1., 1.);
+        // four blades with corners on the axes make a diamond
+        c.aperture_blades = 4;
+        let mut rng = Rng::new(5);
+        let mut far = 0.;
+        for _ in 0..500 {
+            let (x, y) = c.lens_point(rng.next_f64(), rng.next_f64());
+            assert!(x.abs() + y.abs() <= 1. + EPSILON);
+            far = f64::max(far, x.abs().max(y.abs()));
+        }
+        assert!(far > 0.9);
+    }
+
+    #[test]
+    fn depth_of_field_blurs_only_what_is_out_of_focus() {
+        let w = World::default();
+        let mut c = Camera::new(21, 21, PI / 3.);
+        c.set_transform(Matrix4::view_transform(
+            point!(0., 0., -5.),
+            point!(0., 0., 0.),
+            vector!(0., 1., 0.),
+        ));
+        let pinhole = c.render_with_threads(&w, 1);
+        c.lens_samples = 32;
+
+        // focused on the front of the sphere, its middle barely changes
+        c.set_lens(0.3, 4.);
+        let focused = c.render_with_threads(&w, 1);
+        let middle = (focused.pixel_at(10, 10) - pinhole.pixel_at(10, 10))
+            .red
+            .abs();
+        assert!(middle < 0.02, "{middle}");
+
+        // focused far behind it, the sphere's silhouette spreads into the background
+        c.set_lens(0.3, 50.);
+        let blurred = c.render_with_threads(&w, 1);
+        let edge = (0..21)
+            .map(|x| {
+                (blurred.pixel_at(x, 10) - pinhole.pixel_at(x, 10))
+                    .red
+                    .abs()
+            })
+            .fold(0., f64::max);
+        assert!(edge > 0.05, "{edge}");
+    }
+
+    #[test]
+    fn depth_of_field_renders_the_same_on_any_number_of_threads() {
+        let w = World::default();
+        let mut c = Camera::new(19, 13, PI / 3.);
+        c.set_transform(Matrix4::view_transform(
+            point!(0., 1.5, -5.),
+            point!(0., 0., 0.),
+            vector!(0., 1., 0.),
+        ));
+        c.set_lens(0.2, 3.);
+        c.aperture_blades = 6;
+        c.lens_samples = 4;
+        let single = c.render_with_threads(&w, 1);
+        let image = c.render_with_threads(&w, 4);
+        for y in 0..c.vsize() {
+            for x in 0..c.hsize() {
+                assert_eq!(image.pixel_at(x, y), single.pixel_at(x, y));
+            }
+        }
+    }
+
+    #[test]
+    fn pinhole_rays_ignore_the_focal_distance() {
+        let mut c = Camera::new(201, 101, PI / 2.);
+        let expected = c.ray_for_pixel(0, 0);
+        for focal_distance in [0., -3.] {
+            c.set_lens(0., focal_distance);
+            let r = c.ray_for_pixel(0, 0);
+            test_point!(r.origin, expected.origin);
+            test_point!(r.direction, expected.direction);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "focal distance must be positive")]
+    fn lens_rays_need_a_positive_focal_distance() {
+        let mut c = Camera::new(11, 11, PI / 2.);
+        c.set_lens(0.5, 0.);
+    }
+}
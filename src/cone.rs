@@ -0,0 +1,238 @@
+use crate::{
+    bounds::BoundingBox,
+    cylinder::check_cap,
+    equal,
+    intersection::{Intersection, Intersections},
+    material::Material,
+    point,
+    ray::Ray,
+    shape::{Shape, TransformAccess},
+    transform::Transform,
+    tuple::Tuple,
+    vector, EPSILON,
+};
+
+/// A double-napped cone around the y axis with its tips meeting at the origin, truncated
+/// to `minimum..maximum` (exclusive). When `closed` the ends are capped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cone {
+    transform: Transform,
+    pub material: Material,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Default for Cone {
+    fn default() -> Self {
+        Self {
+            transform: Transform::default(),
+            material: Material::default(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+impl Cone {
+    fn intersect_caps<'a>(&'a self, ray: Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+
+        // the radius of a cone's cap is the absolute y value of the plane it sits in
+        let t = (self.minimum - ray.origin.y) / ray.direction.y;
+        if check_cap(ray, t, self.minimum.abs()) {
+            xs.push(Intersection::new(t, self));
+        }
+
+        let t = (self.maximum - ray.origin.y) / ray.direction.y;
+        if check_cap(ray, t, self.maximum.abs()) {
+            xs.push(Intersection::new(t, self));
+        }
+    }
+}
+
+impl Shape for Cone {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+        let mut xs = vec![];
+        let (o, d) = (ray.origin, ray.direction);
+
+        let a = d.x.powi(2) - d.y.powi(2) + d.z.powi(2);
+        let b = 2. * o.x * d.x - 2. * o.y * d.y + 2. * o.z * d.z;
+        let c = o.x.powi(2) - o.y.powi(2) + o.z.powi(2);
+
+        if equal(a, 0.) {
+            // the ray is parallel to one of the halves and crosses the other once
+            if !equal(b, 0.) {
+                let t = -c / (2. * b);
+                let y = o.y + t * d.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        } else {
+            let disc = b * b - 4. * a * c;
+
+            if disc < 0. {
+                return Intersections(xs);
+            }
+
+            let mut t0 = (-b - disc.sqrt()) / (2. * a);
+            let mut t1 = (-b + disc.sqrt()) / (2. * a);
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            for t in [t0, t1] {
+                let y = o.y + t * d.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+        Intersections(xs)
+    }
+
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        let dist = point.x.powi(2) + point.z.powi(2);
+
+        if dist < self.maximum.powi(2) && point.y >= self.maximum - EPSILON {
+            vector!(0., 1., 0.)
+        } else if dist < self.minimum.powi(2) && point.y <= self.minimum + EPSILON {
+            vector!(0., -1., 0.)
+        } else {
+            let y = if point.y > 0. {
+                -dist.sqrt()
+            } else {
+                dist.sqrt()
+            };
+            vector!(point.x, y, point.z)
+        }
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let limit = self.minimum.abs().max(self.maximum.abs());
+
+        BoundingBox::new(
+            point!(-limit, self.minimum, -limit),
+            point!(limit, self.maximum, limit),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bounds::BoundingBox, equal, point, ray::Ray, shape::Shape, test_point, tuple::Tuple, vector,
+    };
+
+    use super::Cone;
+
+    #[test]
+    fn intersecting_a_cone_with_a_ray() {
+        let shape = Cone::default();
+        let cases = [
+            (point!(0., 0., -5.), vector!(0., 0., 1.), 5., 5.),
+            (point!(0., 0., -5.), vector!(1., 1., 1.), 8.66025, 8.66025),
+            (
+                point!(1., 1., -5.),
+                vector!(-0.5, -1., 1.),
+                4.55006,
+                49.44994,
+            ),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let r = Ray {
+                origin,
+                direction: direction.normalize(),
+            };
+            let xs = shape.local_intersect(r);
+            assert_eq!(xs.0.len(), 2);
+            assert!(equal(xs.0[0].t, t0));
+            assert!(equal(xs.0[1].t, t1));
+        }
+    }
+
+    #[test]
+    fn intersecting_a_cone_with_a_ray_parallel_to_one_of_its_halves() {
+        let shape = Cone::default();
+        let r = Ray {
+            origin: point!(0., 0., -1.),
+            direction: vector!(0., 1., 1.).normalize(),
+        };
+        let xs = shape.local_intersect(r);
+        assert_eq!(xs.0.len(), 1);
+        assert!(equal(xs.0[0].t, 0.35355));
+    }
+
+    #[test]
+    fn intersecting_a_cones_end_caps() {
+        let shape = Cone {
+            minimum: -0.5,
+            maximum: 0.5,
+            closed: true,
+            ..Default::default()
+        };
+        let cases = [
+            (point!(0., 0., -5.), vector!(0., 1., 0.), 0),
+            (point!(0., 0., -0.25), vector!(0., 1., 1.), 2),
+            (point!(0., 0., -0.25), vector!(0., 1., 0.), 4),
+        ];
+
+        for (origin, direction, count) in cases {
+            let r = Ray {
+                origin,
+                direction: direction.normalize(),
+            };
+            assert_eq!(shape.local_intersect(r).0.len(), count);
+        }
+    }
+
+    #[test]
+    fn computing_the_normal_vector_on_a_cone() {
+        let shape = Cone::default();
+        let cases = [
+            (point!(0., 0., 0.), vector!(0., 0., 0.)),
+            (point!(1., 1., 1.), vector!(1., -(2_f64.sqrt()), 1.)),
+            (point!(-1., -1., 0.), vector!(-1., 1., 0.)),
+        ];
+
+        for (point, normal) in cases {
+            test_point!(shape.local_normal_at(point), normal);
+        }
+    }
+
+    #[test]
+    fn bounding_box_of_a_truncated_cone() {
+        let shape = Cone {
+            minimum: -5.,
+            maximum: 3.,
+            ..Default::default()
+        };
+        assert_eq!(
+            shape.bounds(),
+            BoundingBox::new(point!(-5., -5., -5.), point!(5., 3., 5.))
+        );
+    }
+}
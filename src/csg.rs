@@ -0,0 +1,297 @@
+use crate::{
+    bounds::BoundingBox,
+    intersection::Intersections,
+    material::Material,
+    matrix::Matrix4,
+    ray::Ray,
+    shape::{Shape, TransformAccess},
+    transform::{ParentTransform, Transform},
+    tuple::Tuple,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl Operation {
+    /// Whether a hit on the left (`lhit`) or right shape survives, given whether the
+    /// ray is currently inside the left (`inl`) and right (`inr`) shapes.
+    pub fn intersection_allowed(&self, lhit: bool, inl: bool, inr: bool) -> bool {
+        match self {
+            Operation::Union => (lhit && !inr) || (!lhit && !inl),
+            Operation::Intersection => (lhit && inr) || (!lhit && inl),
+            Operation::Difference => (lhit && !inr) || (!lhit && inl),
+        }
+    }
+}
+
+/// Constructive solid geometry: two shapes combined by an [`Operation`].
+#[derive(Debug)]
+pub struct Csg {
+    pub operation: Operation,
+    left: Box<dyn Shape>,
+    right: Box<dyn Shape>,
+    transform: Transform,
+    material: Material,
+}
+
+impl Csg {
+    pub fn new(
+        operation: Operation,
+        left: impl Shape + 'static,
+        right: impl Shape + 'static,
+    ) -> Self {
+        Self {
+            operation,
+            left: Box::new(left),
+            right: Box::new(right),
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+
+    pub fn left(&self) -> &dyn Shape {
+        self.left.as_ref()
+    }
+
+    pub fn right(&self) -> &dyn Shape {
+        self.right.as_ref()
+    }
+
+    /// Keeps only the intersections on the surface of the combined shape.
+    pub fn filter_intersections<'a>(&self, xs: Intersections<'a>) -> Intersections<'a> {
+        let mut inl = false;
+        let mut inr = false;
+        let mut result = vec![];
+
+        for i in xs.0 {
+            let lhit = self.left.includes(i.object);
+
+            if self.operation.intersection_allowed(lhit, inl, inr) {
+                result.push(i);
+            }
+
+            if lhit {
+                inl = !inl;
+            } else {
+                inr = !inr;
+            }
+        }
+
+        Intersections(result)
+    }
+
+    fn update_children(&mut self) {
+        let parent = self.transform.as_parent();
+        self.left.set_parent_transform(parent.clone());
+        self.right.set_parent_transform(parent);
+    }
+}
+
+impl Shape for Csg {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+        let mut xs = self.left.intersect(ray);
+        xs.0.extend(self.right.intersect(ray).0);
+        xs.sort();
+        self.filter_intersections(xs)
+    }
+
+    /// Rays never report a CSG shape as the object they hit, so it has no normal of its own.
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        unreachable!("normals are computed on the operands")
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn set_transform(&mut self, t: Matrix4) {
+        self.transform.set(t);
+        self.update_children();
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_parent_transform(&mut self, parent: ParentTransform) {
+        self.transform.set_parent(parent);
+        self.update_children();
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.left.includes(other) || self.right.includes(other)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut b = self.left.parent_space_bounds();
+        b.merge(self.right.parent_space_bounds());
+        b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bounds::BoundingBox,
+        cube::Cube,
+        group::Group,
+        intersection::{Intersection, Intersections},
+        matrix::Matrix4,
+        point,
+        ray::Ray,
+        shape::Shape,
+        sphere::Sphere,
+        tuple::Tuple,
+        vector,
+    };
+
+    use super::{Csg, Operation};
+
+    #[test]
+    fn csg_is_created_with_an_operation_and_two_shapes() {
+        let mut c = Csg::new(Operation::Union, Sphere::default(), Cube::default());
+        c.set_transform(Matrix4::translate(1., 0., 0.));
+        assert_eq!(c.operation, Operation::Union);
+        assert_eq!(c.left().parent_transform(), Matrix4::translate(1., 0., 0.));
+        assert_eq!(c.right().parent_transform(), Matrix4::translate(1., 0., 0.));
+    }
+
+    #[test]
+    fn evaluating_rule_for_csg_operations() {
+        let cases = [
+            (Operation::Union, true, true, true, false),
+            (Operation::Union, true, true, false, true),
+            (Operation::Union, true, false, true, false),
+            (Operation::Union, true, false, false, true),
+            (Operation::Union, false, true, true, false),
+            (Operation::Union, false, true, false, false),
+            (Operation::Union, false, false, true, true),
+            (Operation::Union, false, false, false, true),
+            (Operation::Intersection, true, true, true, true),
+            (Operation::Intersection, true, true, false, false),
+            (Operation::Intersection, true, false, true, true),
+            (Operation::Intersection, true, false, false, false),
+            (Operation::Intersection, false, true, true, true),
+            (Operation::Intersection, false, true, false, true),
+            (Operation::Intersection, false, false, true, false),
+            (Operation::Intersection, false, false, false, false),
+            (Operation::Difference, true, true, true, false),
+            (Operation::Difference, true, true, false, true),
+            (Operation::Difference, true, false, true, false),
+            (Operation::Difference, true, false, false, true),
+            (Operation::Difference, false, true, true, true),
+            (Operation::Difference, false, true, false, true),
+            (Operation::Difference, false, false, true, false),
+            (Operation::Difference, false, false, false, false),
+        ];
+
+        for (op, lhit, inl, inr, result) in cases {
+            assert_eq!(op.intersection_allowed(lhit, inl, inr), result);
+        }
+    }
+
+    #[test]
+    fn filtering_a_list_of_intersections() {
+        let cases = [
+            (Operation::Union, 0, 3),
+            (Operation::Intersection, 1, 2),
+            (Operation::Difference, 0, 1),
+        ];
+
+        for (op, x0, x1) in cases {
+            let c = Csg::new(op, Sphere::default(), Cube::default());
+            let (s1, s2) = (c.left(), c.right());
+            let xs = Intersections(vec![
+                Intersection::new(1., s1),
+                Intersection::new(2., s2),
+                Intersection::new(3., s1),
+                Intersection::new(4., s2),
+            ]);
+            let result = c.filter_intersections(xs.clone());
+            assert_eq!(result.0.len(), 2);
+            assert_eq!(result.0[0], xs.0[x0]);
+            assert_eq!(result.0[1], xs.0[x1]);
+        }
+    }
+
+    #[test]
+    fn ray_misses_a_csg_object() {
+        let c = Csg::new(Operation::Union, Sphere::default(), Cube::default());
+        let r = Ray {
+            origin: point!(0., 2., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        assert!(c.local_intersect(r).0.is_empty());
+    }
+
+    #[test]
+    fn ray_hits_a_csg_object() {
+        let mut s2 = Sphere::default();
+        s2.set_transform(Matrix4::translate(0., 0., 0.5));
+        let c = Csg::new(Operation::Union, Sphere::default(), s2);
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = c.local_intersect(r);
+        assert_eq!(xs.0.len(), 2);
+        assert_eq!(xs.0[0].t, 4.);
+        assert_eq!(xs.0[0].object, c.left());
+        assert_eq!(xs.0[1].t, 6.5);
+        assert_eq!(xs.0[1].object, c.right());
+    }
+
+    #[test]
+    fn difference_hollows_out_the_left_shape() {
+        let mut hole = Sphere::default();
+        hole.set_transform(Matrix4::scaling(0.5, 0.5, 0.5));
+        let c = Csg::new(Operation::Difference, Cube::default(), hole);
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let ts: Vec<f64> = c.intersect(r).0.iter().map(|i| i.t).collect();
+        assert_eq!(ts, vec![4., 4.5, 5.5, 6.]);
+    }
+
+    #[test]
+    fn csg_operands_can_be_groups() {
+        let mut s = Sphere::default();
+        s.set_transform(Matrix4::scaling(0.5, 0.5, 0.5));
+        let mut g = Group::default();
+        g.add_child(s);
+        let c = Csg::new(Operation::Intersection, g, Cube::default());
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = c.intersect(r);
+        assert_eq!(xs.0.len(), 2);
+        assert_eq!(xs.0[0].t, 4.5);
+        assert!(c.left().includes(xs.0[0].object));
+        assert!(!c.right().includes(xs.0[0].object));
+    }
+
+    #[test]
+    fn csg_has_bounding_box_that_contains_its_children() {
+        let mut right = Sphere::default();
+        right.set_transform(Matrix4::translate(2., 3., 4.));
+        let c = Csg::new(Operation::Difference, Sphere::default(), right);
+        assert_eq!(
+            c.bounds(),
+            BoundingBox::new(point!(-1., -1., -1.), point!(3., 4., 5.))
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::{
+    bounds::BoundingBox,
+    intersection::{Intersection, Intersections},
+    material::Material,
+    point,
+    ray::Ray,
+    shape::{Shape, TransformAccess},
+    transform::Transform,
+    tuple::Tuple,
+    vector, EPSILON,
+};
+
+/// An axis-aligned cube spanning -1 to 1 on every axis.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Cube {
+    transform: Transform,
+    pub material: Material,
+}
+
+/// Where a ray enters and leaves the slab between `min` and `max` along one axis.
+pub(crate) fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    let tmin_numerator = min - origin;
+    let tmax_numerator = max - origin;
+
+    let (tmin, tmax) = if direction.abs() >= EPSILON {
+        (tmin_numerator / direction, tmax_numerator / direction)
+    } else {
+        (
+            tmin_numerator * f64::INFINITY,
+            tmax_numerator * f64::INFINITY,
+        )
+    };
+
+    if tmin > tmax {
+        (tmax, tmin)
+    } else {
+        (tmin, tmax)
+    }
+}
+
+impl Shape for Cube {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+        let (xtmin, xtmax) = check_axis(ray.origin.x, ray.direction.x, -1., 1.);
+        let (ytmin, ytmax) = check_axis(ray.origin.y, ray.direction.y, -1., 1.);
+        let (ztmin, ztmax) = check_axis(ray.origin.z, ray.direction.z, -1., 1.);
+
+        let tmin = xtmin.max(ytmin).max(ztmin);
+        let tmax = xtmax.min(ytmax).min(ztmax);
+
+        if tmin > tmax {
+            return Intersections(vec![]);
+        }
+
+        Intersections(vec![
+            Intersection::new(tmin, self),
+            Intersection::new(tmax, self),
+        ])
+    }
+
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        let maxc = point.x.abs().max(point.y.abs()).max(point.z.abs());
+
+        if maxc == point.x.abs() {
+            vector!(point.x, 0., 0.)
+        } else if maxc == point.y.abs() {
+            vector!(0., point.y, 0.)
+        } else {
+            vector!(0., 0., point.z)
+        }
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(point!(-1., -1., -1.), point!(1., 1., 1.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{matrix::Matrix4, point, ray::Ray, shape::Shape, tuple::Tuple, vector};
+
+    use super::Cube;
+
+    #[test]
+    fn ray_intersects_a_cube() {
+        let c = Cube::default();
+        let cases = [
+            (point!(5., 0.5, 0.), vector!(-1., 0., 0.), 4., 6.),
+            (point!(-5., 0.5, 0.), vector!(1., 0., 0.), 4., 6.),
+            (point!(0.5, 5., 0.), vector!(0., -1., 0.), 4., 6.),
+            (point!(0.5, -5., 0.), vector!(0., 1., 0.), 4., 6.),
+            (point!(0.5, 0., 5.), vector!(0., 0., -1.), 4., 6.),
+            (point!(0.5, 0., -5.), vector!(0., 0., 1.), 4., 6.),
+            (point!(0., 0.5, 0.), vector!(0., 0., 1.), -1., 1.),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            let xs = c.local_intersect(Ray { origin, direction });
+            assert_eq!(xs.0.len(), 2);
+            assert_eq!(xs.0[0].t, t1);
+            assert_eq!(xs.0[1].t, t2);
+        }
+    }
+
+    #[test]
+    fn ray_misses_a_cube() {
+        let c = Cube::default();
+        let cases = [
+            (point!(-2., 0., 0.), vector!(0.2673, 0.5345, 0.8018)),
+            (point!(0., -2., 0.), vector!(0.8018, 0.2673, 0.5345)),
+            (point!(0., 0., -2.), vector!(0.5345, 0.8018, 0.2673)),
+            (point!(2., 0., 2.), vector!(0., 0., -1.)),
+            (point!(0., 2., 2.), vector!(0., -1., 0.)),
+            (point!(2., 2., 0.), vector!(-1., 0., 0.)),
+        ];
+
+        for (origin, direction) in cases {
+            let xs = c.local_intersect(Ray { origin, direction });
+            assert!(xs.0.is_empty());
+        }
+    }
+
+    #[test]
+    fn normal_on_surface_of_cube() {
+        let c = Cube::default();
+        let cases = [
+            (point!(1., 0.5, -0.8), vector!(1., 0., 0.)),
+            (point!(-1., -0.2, 0.9), vector!(-1., 0., 0.)),
+            (point!(-0.4, 1., -0.1), vector!(0., 1., 0.)),
+            (point!(0.3, -1., -0.7), vector!(0., -1., 0.)),
+            (point!(-0.6, 0.3, 1.), vector!(0., 0., 1.)),
+            (point!(0.4, 0.4, -1.), vector!(0., 0., -1.)),
+            (point!(1., 1., 1.), vector!(1., 0., 0.)),
+            (point!(-1., -1., -1.), vector!(-1., 0., 0.)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(c.local_normal_at(point), normal);
+        }
+    }
+
+    #[test]
+    fn intersecting_scaled_cube() {
+        let mut c = Cube::default();
+        c.set_transform(Matrix4::scaling(2., 1., 1.));
+        let r = Ray {
+            origin: point!(-5., 0.5, 0.),
+            direction: vector!(1., 0., 0.),
+        };
+        let xs = c.intersect(r);
+        assert_eq!(xs.0.len(), 2);
+        assert_eq!(xs.0[0].t, 3.);
+        assert_eq!(xs.0[1].t, 7.);
+        assert_eq!(c.normal_at(r.position(3.)), vector!(-1., 0., 0.));
+    }
+}
@@ -0,0 +1,281 @@
+use crate::{
+    bounds::BoundingBox,
+    equal,
+    intersection::{Intersection, Intersections},
+    material::Material,
+    point,
+    ray::Ray,
+    shape::{Shape, TransformAccess},
+    transform::Transform,
+    tuple::Tuple,
+    vector, EPSILON,
+};
+
+/// A cylinder of radius 1 around the y axis, truncated to `minimum..maximum` (exclusive).
+/// When `closed` the ends are capped.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cylinder {
+    transform: Transform,
+    pub material: Material,
+    pub minimum: f64,
+    pub maximum: f64,
+    pub closed: bool,
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self {
+            transform: Transform::default(),
+            material: Material::default(),
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+        }
+    }
+}
+
+/// Whether the ray at `t` lies within `radius` of the y axis.
+pub(crate) fn check_cap(ray: Ray, t: f64, radius: f64) -> bool {
+    let x = ray.origin.x + t * ray.direction.x;
+    let z = ray.origin.z + t * ray.direction.z;
+
+    x * x + z * z <= radius * radius
+}
+
+impl Cylinder {
+    fn intersect_caps<'a>(&'a self, ray: Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || ray.direction.y.abs() < EPSILON {
+            return;
+        }
+
+        let t = (self.minimum - ray.origin.y) / ray.direction.y;
+        if check_cap(ray, t, 1.) {
+            xs.push(Intersection::new(t, self));
+        }
+
+        let t = (self.maximum - ray.origin.y) / ray.direction.y;
+        if check_cap(ray, t, 1.) {
+            xs.push(Intersection::new(t, self));
+        }
+    }
+}
+
+impl Shape for Cylinder {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+        let mut xs = vec![];
+        let a = ray.direction.x.powi(2) + ray.direction.z.powi(2);
+
+        // rays parallel to the y axis can only hit the caps
+        if !equal(a, 0.) {
+            let b = 2. * ray.origin.x * ray.direction.x + 2. * ray.origin.z * ray.direction.z;
+            let c = ray.origin.x.powi(2) + ray.origin.z.powi(2) - 1.;
+            let disc = b * b - 4. * a * c;
+
+            if disc < 0. {
+                return Intersections(xs);
+            }
+
+            let mut t0 = (-b - disc.sqrt()) / (2. * a);
+            let mut t1 = (-b + disc.sqrt()) / (2. * a);
+            if t0 > t1 {
+                std::mem::swap(&mut t0, &mut t1);
+            }
+
+            for t in [t0, t1] {
+                let y = ray.origin.y + t * ray.direction.y;
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+        Intersections(xs)
+    }
+
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        let dist = point.x.powi(2) + point.z.powi(2);
+
+        if dist < 1. && point.y >= self.maximum - EPSILON {
+            vector!(0., 1., 0.)
+        } else if dist < 1. && point.y <= self.minimum + EPSILON {
+            vector!(0., -1., 0.)
+        } else {
+            vector!(point.x, 0., point.z)
+        }
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(point!(-1., self.minimum, -1.), point!(1., self.maximum, 1.))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{bounds::BoundingBox, equal, point, ray::Ray, shape::Shape, tuple::Tuple, vector};
+
+    use super::Cylinder;
+
+    #[test]
+    fn ray_misses_a_cylinder() {
+        let cyl = Cylinder::default();
+        let cases = [
+            (point!(1., 0., 0.), vector!(0., 1., 0.)),
+            (point!(0., 0., 0.), vector!(0., 1., 0.)),
+            (point!(0., 0., -5.), vector!(1., 1., 1.)),
+        ];
+
+        for (origin, direction) in cases {
+            let r = Ray {
+                origin,
+                direction: direction.normalize(),
+            };
+            assert!(cyl.local_intersect(r).0.is_empty());
+        }
+    }
+
+    #[test]
+    fn ray_strikes_a_cylinder() {
+        let cyl = Cylinder::default();
+        let cases = [
+            (point!(1., 0., -5.), vector!(0., 0., 1.), 5., 5.),
+            (point!(0., 0., -5.), vector!(0., 0., 1.), 4., 6.),
+            (point!(0.5, 0., -5.), vector!(0.1, 1., 1.), 6.80798, 7.08872),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            let r = Ray {
+                origin,
+                direction: direction.normalize(),
+            };
+            let xs = cyl.local_intersect(r);
+            assert_eq!(xs.0.len(), 2);
+            assert!(equal(xs.0[0].t, t0));
+            assert!(equal(xs.0[1].t, t1));
+        }
+    }
+
+    #[test]
+    fn normal_vector_on_a_cylinder() {
+        let cyl = Cylinder::default();
+        let cases = [
+            (point!(1., 0., 0.), vector!(1., 0., 0.)),
+            (point!(0., 5., -1.), vector!(0., 0., -1.)),
+            (point!(0., -2., 1.), vector!(0., 0., 1.)),
+            (point!(-1., 1., 0.), vector!(-1., 0., 0.)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(cyl.local_normal_at(point), normal);
+        }
+    }
+
+    #[test]
+    fn default_minimum_and_maximum_for_a_cylinder() {
+        let cyl = Cylinder::default();
+        assert_eq!(cyl.minimum, f64::NEG_INFINITY);
+        assert_eq!(cyl.maximum, f64::INFINITY);
+        assert!(!cyl.closed);
+    }
+
+    #[test]
+    fn intersecting_a_constrained_cylinder() {
+        let cyl = Cylinder {
+            minimum: 1.,
+            maximum: 2.,
+            ..Default::default()
+        };
+        let cases = [
+            (point!(0., 1.5, 0.), vector!(0.1, 1., 0.), 0),
+            (point!(0., 3., -5.), vector!(0., 0., 1.), 0),
+            (point!(0., 0., -5.), vector!(0., 0., 1.), 0),
+            (point!(0., 2., -5.), vector!(0., 0., 1.), 0),
+            (point!(0., 1., -5.), vector!(0., 0., 1.), 0),
+            (point!(0., 1.5, -2.), vector!(0., 0., 1.), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let r = Ray {
+                origin,
+                direction: direction.normalize(),
+            };
+            assert_eq!(cyl.local_intersect(r).0.len(), count);
+        }
+    }
+
+    #[test]
+    fn intersecting_the_caps_of_a_closed_cylinder() {
+        let cyl = Cylinder {
+            minimum: 1.,
+            maximum: 2.,
+            closed: true,
+            ..Default::default()
+        };
+        let cases = [
+            (point!(0., 3., 0.), vector!(0., -1., 0.), 2),
+            (point!(0., 3., -2.), vector!(0., -1., 2.), 2),
+            (point!(0., 4., -2.), vector!(0., -1., 1.), 2),
+            (point!(0., 0., -2.), vector!(0., 1., 2.), 2),
+            (point!(0., -1., -2.), vector!(0., 1., 1.), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            let r = Ray {
+                origin,
+                direction: direction.normalize(),
+            };
+            assert_eq!(cyl.local_intersect(r).0.len(), count);
+        }
+    }
+
+    #[test]
+    fn normal_vector_on_a_cylinders_end_caps() {
+        let cyl = Cylinder {
+            minimum: 1.,
+            maximum: 2.,
+            closed: true,
+            ..Default::default()
+        };
+        let cases = [
+            (point!(0., 1., 0.), vector!(0., -1., 0.)),
+            (point!(0.5, 1., 0.), vector!(0., -1., 0.)),
+            (point!(0., 1., 0.5), vector!(0., -1., 0.)),
+            (point!(0., 2., 0.), vector!(0., 1., 0.)),
+            (point!(0.5, 2., 0.), vector!(0., 1., 0.)),
+            (point!(0., 2., 0.5), vector!(0., 1., 0.)),
+        ];
+
+        for (point, normal) in cases {
+            assert_eq!(cyl.local_normal_at(point), normal);
+        }
+    }
+
+    #[test]
+    fn bounding_box_of_a_truncated_cylinder() {
+        let cyl = Cylinder {
+            minimum: -5.,
+            maximum: 3.,
+            ..Default::default()
+        };
+        assert_eq!(
+            cyl.bounds(),
+            BoundingBox::new(point!(-1., -5., -1.), point!(1., 3., 1.))
+        );
+    }
+}
@@ -0,0 +1,201 @@
+use std::{f64::consts::PI, sync::Arc};
+
+use crate::{
+    light::{rng_at, Light, LightSample},
+    texture::{ImageTexture, UvMapping, UvPattern},
+    tuple::Tuple,
+    vector, Canvas, Color, BLACK,
+};
+
+/// Perceived brightness, used to decide where to aim samples.
+fn luminance(color: Color) -> f64 {
+    0.2126 * color.red + 0.7152 * color.green + 0.0722 * color.blue
+}
+
+/// A distant panorama lighting the scene from every direction, from an equirectangular
+/// image: longitude across, straight up along the top row. Rays that miss everything
+/// see it as the background.
+#[derive(Debug, Clone)]
+pub struct EnvironmentLight {
+    texture: ImageTexture,
+    /// How many directions are sampled at each shaded point.
+    pub samples: usize,
+    /// Each pixel's share of the light, cumulated row by row so a sample can be drawn by
+    /// binary search.
+    cdf: Arc<[f64]>,
+}
+
+impl EnvironmentLight {
+    /// Panics if `image` has no pixels, since there'd be nothing to light the scene with.
+    pub fn new(image: Canvas, samples: usize) -> Self {
+        let (width, height) = (image.width(), image.height());
+        assert!(
+            width > 0 && height > 0,
+            "environment image must not be empty"
+        );
+        let mut total = 0.;
+        let mut cdf = Vec::with_capacity(width * height);
+        for y in 0..height {
+            // rows near the poles cover less of the sphere
+            let sin_theta = ((y as f64 + 0.5) / height as f64 * PI).sin();
+            for x in 0..width {
+                total += luminance(image.pixel_at(x, y)).max(0.) * sin_theta;
+                cdf.push(total);
+            }
+        }
+        if total > 0. {
+            cdf.iter_mut().for_each(|c| *c /= total);
+        } else {
+            // a black image: spread samples evenly, though they'll carry nothing
+            cdf.iter_mut()
+                .enumerate()
+                .for_each(|(i, c)| *c = (i + 1) as f64 / (width * height) as f64);
+        }
+
+        Self {
+            texture: ImageTexture::new(Arc::new(image)),
+            samples,
+            cdf: cdf.into(),
+        }
+    }
+
+    pub fn image(&self) -> &Canvas {
+        &self.texture.image
+    }
+
+    /// The panorama seen looking along `direction`, smoothly filtered.
+    pub fn color_in(&self, direction: Tuple) -> Color {
+        let (u, v) = UvMapping::Spherical.map(direction);
+        self.texture.uv_pattern_at(u, v)
+    }
+
+    /// The direction through a spot inside pixel `(x, y)`, with `(du, dv)` in `0..1`
+    /// placing it within the pixel, and its angle down from straight up.
+    fn direction(&self, x: usize, y: usize, du: f64, dv: f64) -> (Tuple, f64) {
+        let image = self.image();
+        let u = (x as f64 + du) / image.width() as f64;
+        let theta = (y as f64 + dv) / image.height() as f64 * PI;
+        // inverse of the spherical mapping's longitude
+        let phi = (0.5 - u) * 2. * PI;
+        let direction = vector!(
+            theta.sin() * phi.sin(),
+            theta.cos(),
+            theta.sin() * phi.cos()
+        );
+        (direction, theta)
+    }
+
+    /// How likely a sample at angle `theta` down from straight up is to have come from
+    /// pixel `(x, y)`, per steradian.
+    fn pdf(&self, x: usize, y: usize, theta: f64) -> f64 {
+        let image = self.image();
+        let i = y * image.width() + x;
+        let probability = self.cdf[i] - if i == 0 { 0. } else { self.cdf[i - 1] };
+        let pixel_area = (2. * PI / image.width() as f64) * (PI / image.height() as f64);
+        probability / (pixel_area * theta.sin())
+    }
+}
+
+impl Light for EnvironmentLight {
+    /// Nothing: the environment is all around, so it's lit through its samples alone.
+    fn intensity(&self) -> Color {
+        BLACK
+    }
+
+    /// Directions picked in proportion to the panorama's brightness, stratified so they
+    /// spread out. Each is weighted by how likely it was, so a uniform white environment
+    /// lights a surface as brightly as a white light shining straight at it.
+    fn samples(&self, point: Tuple) -> Vec<LightSample> {
+        let mut rng = rng_at(point);
+        let width = self.image().width();
+
+        (0..self.samples)
+            .map(|i| {
+                let target = (i as f64 + rng.next_f64()) / self.samples as f64;
+                let index = self
+                    .cdf
+                    .partition_point(|&c| c < target)
+                    .min(self.cdf.len() - 1);
+                let (x, y) = (index % width, index / width);
+                let (direction, theta) = self.direction(x, y, rng.next_f64(), rng.next_f64());
+                LightSample {
+                    direction,
+                    distance: f64::INFINITY,
+                    intensity: self.image().pixel_at(x, y) / (PI * self.pdf(x, y, theta)),
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{equal, light::Light, point, test_color, tuple::Tuple, vector, Canvas, Color};
+
+    use super::EnvironmentLight;
+
+    fn uniform(color: Color) -> Canvas {
+        let mut image = Canvas::new(8, 4);
+        for y in 0..4 {
+            for x in 0..8 {
+                image.write_pixel(x, y, color);
+            }
+        }
+        image
+    }
+
+    #[test]
+    fn background_color_follows_the_view_direction() {
+        let mut image = uniform(Color::new(0., 0., 1.));
+        for x in 0..8 {
+            image.write_pixel(x, 0, Color::new(1., 1., 1.));
+        }
+        let env = EnvironmentLight::new(image, 4);
+        test_color!(env.color_in(vector!(0., 1., 0.)), Color::new(1., 1., 1.));
+        test_color!(env.color_in(vector!(0., -1., 0.)), Color::new(0., 0., 1.));
+    }
+
+    #[test]
+    #[should_panic(expected = "must not be empty")]
+    fn empty_images_are_rejected() {
+        EnvironmentLight::new(Canvas::new(0, 0), 4);
+    }
+
+    #[test]
+    fn samples_are_distant_unit_directions() {
+        let env = EnvironmentLight::new(uniform(Color::new(1., 1., 1.)), 16);
+        let samples = env.samples(point!(1., 2., 3.));
+        assert_eq!(samples.len(), 16);
+        for sample in &samples {
+            assert!(equal(sample.direction.magnitude(), 1.));
+            assert_eq!(sample.distance, f64::INFINITY);
+        }
+        assert_eq!(samples, env.samples(point!(1., 2., 3.)));
+    }
+
+    #[test]
+    fn uniform_environment_averages_to_its_color() {
+        let env = EnvironmentLight::new(uniform(Color::new(1., 1., 1.)), 4096);
+        let samples = env.samples(point!(0., 0., 0.));
+        // irradiance on an upward-facing surface, which a white light overhead gives as 1
+        let irradiance = samples
+            .iter()
+            .map(|s| s.intensity.red * s.direction.y.max(0.))
+            .sum::<f64>()
+            / samples.len() as f64;
+        assert!((irradiance - 1.).abs() < 0.05, "{irradiance}");
+    }
+
+    #[test]
+    fn samples_favour_bright_regions() {
+        let mut image = uniform(Color::new(0.01, 0.01, 0.01));
+        image.write_pixel(2, 1, Color::new(100., 100., 100.));
+        let env = EnvironmentLight::new(image, 100);
+        let toward_sun = env
+            .samples(point!(0., 0., 0.))
+            .iter()
+            .filter(|s| env.color_in(s.direction).red > 1.)
+            .count();
+        assert!(toward_sun > 80, "{toward_sun}");
+    }
+}
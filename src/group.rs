@@ -0,0 +1,260 @@
+use std::sync::OnceLock;
+
+use crate::{
+    bounds::BoundingBox,
+    bvh::Bvh,
+    intersection::Intersections,
+    material::Material,
+    matrix::Matrix4,
+    ray::Ray,
+    shape::{Shape, TransformAccess},
+    transform::{ParentTransform, Transform},
+    tuple::Tuple,
+};
+
+/// A collection of shapes moved as one. The group's transform applies on top of
+/// each child's own transform.
+#[derive(Debug)]
+pub struct Group {
+    transform: Transform,
+    material: Material,
+    children: Vec<Box<dyn Shape>>,
+    /// Built over the children on the first ray, so large meshes aren't tested triangle by triangle.
+    bvh: OnceLock<Bvh>,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Self {
+            transform: Transform::default(),
+            material: Material::default(),
+            children: vec![],
+            bvh: OnceLock::new(),
+        }
+    }
+}
+
+impl Group {
+    pub fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+
+    pub fn add_child(&mut self, child: impl Shape + 'static) {
+        self.add_boxed_child(Box::new(child));
+    }
+
+    pub fn add_boxed_child(&mut self, mut child: Box<dyn Shape>) {
+        child.set_parent_transform(self.transform.as_parent());
+        self.children.push(child);
+        self.bvh.take();
+    }
+
+    fn update_children(&mut self) {
+        let parent = self.transform.as_parent();
+        for child in &mut self.children {
+            child.set_parent_transform(parent.clone());
+        }
+    }
+}
+
+impl Shape for Group {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+        self.bvh
+            .get_or_init(|| Bvh::build(&self.children))
+            .intersect(&self.children, ray)
+    }
+
+    /// Rays never report a group as the object they hit, so it has no normal of its own.
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        unreachable!("normals are computed on the group's children")
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn set_transform(&mut self, t: Matrix4) {
+        self.transform.set(t);
+        self.update_children();
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn set_parent_transform(&mut self, parent: ParentTransform) {
+        self.transform.set_parent(parent);
+        self.update_children();
+    }
+
+    fn includes(&self, other: &dyn Shape) -> bool {
+        self.children.iter().any(|child| child.includes(other))
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut b = BoundingBox::default();
+        for child in &self.children {
+            b.merge(child.parent_space_bounds());
+        }
+        b
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        bounds::BoundingBox, cylinder::Cylinder, equal, matrix::Matrix4, point, ray::Ray,
+        shape::Shape, sphere::Sphere, test_point, tuple::Tuple, vector,
+    };
+
+    use super::Group;
+
+    #[test]
+    fn creating_a_new_group() {
+        let g = Group::default();
+        assert_eq!(g.transform(), Matrix4::identity());
+        assert!(g.children().is_empty());
+    }
+
+    #[test]
+    fn adding_a_child_to_a_group() {
+        let mut g = Group::default();
+        g.set_transform(Matrix4::translate(1., 0., 0.));
+        g.add_child(Sphere::default());
+        assert_eq!(g.children().len(), 1);
+        assert_eq!(
+            g.children()[0].parent_transform(),
+            Matrix4::translate(1., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn intersecting_ray_with_empty_group() {
+        let g = Group::default();
+        let r = Ray {
+            origin: point!(0., 0., 0.),
+            direction: vector!(0., 0., 1.),
+        };
+        assert!(g.local_intersect(r).0.is_empty());
+    }
+
+    #[test]
+    fn intersecting_ray_with_nonempty_group() {
+        let mut g = Group::default();
+        let s1 = Sphere::default();
+        let mut s2 = Sphere::default();
+        s2.set_transform(Matrix4::translate(0., 0., -3.));
+        let mut s3 = Sphere::default();
+        s3.set_transform(Matrix4::translate(5., 0., 0.));
+        g.add_child(s1);
+        g.add_child(s2);
+        g.add_child(s3);
+
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = g.local_intersect(r);
+        let children = g.children();
+        assert_eq!(xs.0.len(), 4);
+        assert_eq!(xs.0[0].object, children[1].as_ref());
+        assert_eq!(xs.0[1].object, children[1].as_ref());
+        assert_eq!(xs.0[2].object, children[0].as_ref());
+        assert_eq!(xs.0[3].object, children[0].as_ref());
+    }
+
+    #[test]
+    fn intersecting_transformed_group() {
+        let mut g = Group::default();
+        g.set_transform(Matrix4::scaling(2., 2., 2.));
+        let mut s = Sphere::default();
+        s.set_transform(Matrix4::translate(5., 0., 0.));
+        g.add_child(s);
+        let r = Ray {
+            origin: point!(10., 0., -10.),
+            direction: vector!(0., 0., 1.),
+        };
+        assert_eq!(g.intersect(r).0.len(), 2);
+    }
+
+    /// A sphere translated by (5, 0, 0) inside a group scaled by `scale`, itself
+    /// inside a group rotated a quarter turn around y.
+    fn nested_groups(scale: Matrix4) -> Group {
+        let mut g2 = Group::default();
+        g2.set_transform(scale);
+        let mut s = Sphere::default();
+        s.set_transform(Matrix4::translate(5., 0., 0.));
+        g2.add_child(s);
+
+        let mut g1 = Group::default();
+        g1.add_child(g2);
+        g1.set_transform(Matrix4::rotation_y(PI / 2.));
+        g1
+    }
+
+    // the innermost sphere always sits on the negative z axis
+    fn ray_at_sphere() -> Ray {
+        Ray {
+            origin: point!(0., 0., -20.),
+            direction: vector!(0., 0., 1.),
+        }
+    }
+
+    #[test]
+    fn converting_point_from_world_to_object_space() {
+        let g1 = nested_groups(Matrix4::scaling(2., 2., 2.));
+        let xs = g1.intersect(ray_at_sphere());
+        let s = xs.hit().unwrap().object;
+        test_point!(
+            s.world_to_object(point!(-2., 0., -10.)),
+            point!(0., 0., -1.)
+        );
+    }
+
+    #[test]
+    fn converting_normal_from_object_to_world_space() {
+        let g1 = nested_groups(Matrix4::scaling(1., 2., 3.));
+        let xs = g1.intersect(ray_at_sphere());
+        let s = xs.hit().unwrap().object;
+        let v = 3_f64.sqrt() / 3.;
+        let n = s.normal_to_world(vector!(v, v, v));
+        test_point!(n, vector!(0.28571, 0.42857, -0.85714));
+    }
+
+    #[test]
+    fn finding_normal_on_child_object() {
+        let g1 = nested_groups(Matrix4::scaling(1., 2., 3.));
+        let xs = g1.intersect(ray_at_sphere());
+        let s = xs.hit().unwrap().object;
+        let n = s.normal_at(point!(1.7321, 1.1547, -5.5774));
+        test_point!(n, vector!(0.2857, 0.42854, -0.85716));
+    }
+
+    #[test]
+    fn group_has_bounding_box_that_contains_its_children() {
+        let mut s = Sphere::default();
+        s.set_transform(Matrix4::translate(2., 5., -3.) * Matrix4::scaling(2., 2., 2.));
+        let mut c = Cylinder::default();
+        c.minimum = -2.;
+        c.maximum = 2.;
+        c.set_transform(Matrix4::translate(-4., -1., 4.) * Matrix4::scaling(0.5, 1., 0.5));
+        let mut g = Group::default();
+        g.add_child(s);
+        g.add_child(c);
+        let b = g.bounds();
+        assert_eq!(
+            b,
+            BoundingBox::new(point!(-4.5, -3., -5.), point!(4., 7., 4.5))
+        );
+    }
+}
@@ -0,0 +1,250 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use crate::{Canvas, Color};
+
+/// Why a Radiance HDR image couldn't be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HdrError {
+    pub message: String,
+}
+
+impl Display for HdrError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid HDR: {}", self.message)
+    }
+}
+
+impl std::error::Error for HdrError {}
+
+fn error(message: impl Into<String>) -> HdrError {
+    HdrError {
+        message: message.into(),
+    }
+}
+
+/// Reads the raster one byte at a time, failing cleanly if it runs out.
+struct Bytes<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Bytes<'a> {
+    fn next(&mut self) -> Result<u8, HdrError> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or_else(|| error("pixel data is cut short"))?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    fn line(&mut self) -> Result<&'a str, HdrError> {
+        let bytes = self.bytes;
+        let rest = &bytes[self.position..];
+        let end = rest
+            .iter()
+            .position(|&b| b == b'\n')
+            .ok_or_else(|| error("header is cut short"))?;
+        self.position += end + 1;
+        std::str::from_utf8(&rest[..end]).map_err(|_| error("header isn't text"))
+    }
+
+    /// One scanline of `width` RGBE pixels, either flat or run-length encoded per channel.
+    fn scanline(&mut self, width: usize) -> Result<Vec<[u8; 4]>, HdrError> {
+        let mut pixels = vec![[0; 4]; width];
+        let header = self.bytes.get(self.position..self.position + 4);
+        let encoded = (8..=0x7fff).contains(&width)
+            && matches!(header, Some(&[2, 2, hi, lo]) if (hi as usize) << 8 | lo as usize == width);
+
+        if !encoded {
+            for pixel in &mut pixels {
+                for channel in pixel.iter_mut() {
+                    *channel = self.next()?;
+                }
+            }
+            return Ok(pixels);
+        }
+
+        self.position += 4;
+        for channel in 0..4 {
+            let mut x = 0;
+            while x < width {
+                let count = self.next()? as usize;
+                let (count, run) = if count > 128 {
+                    (count - 128, Some(self.next()?))
+                } else {
+                    (count, None)
+                };
+                if count == 0 || x + count > width {
+                    return Err(error("bad run length"));
+                }
+                for pixel in &mut pixels[x..x + count] {
+                    pixel[channel] = match run {
+                        Some(value) => value,
+                        None => self.next()?,
+                    };
+                }
+                x += count;
+            }
+        }
+        Ok(pixels)
+    }
+}
+
+/// A shared-exponent RGBE pixel as linear color.
+fn rgbe_to_color([r, g, b, e]: [u8; 4]) -> Color {
+    if e == 0 {
+        return Color::new(0., 0., 0.);
+    }
+    let scale = 2_f64.powi(e as i32 - 136);
+    Color::new(r as f64 * scale, g as f64 * scale, b as f64 * scale)
+}
+
+impl Canvas {
+    /// Parses a Radiance RGBE (`.hdr`) image stored top row first, the usual `-Y h +X w`
+    /// layout. Colors are linear and may be well above 1.0.
+    pub fn from_hdr(bytes: &[u8]) -> Result<Self, HdrError> {
+        let mut reader = Bytes { bytes, position: 0 };
+        if !reader.line()?.starts_with("#?") {
+            return Err(error("expected a #?RADIANCE header"));
+        }
+        loop {
+            let line = reader.line()?;
+            if line.is_empty() {
+                break;
+            }
+            if let Some(format) = line.strip_prefix("FORMAT=") {
+                if format != "32-bit_rle_rgbe" {
+                    return Err(error(format!("unsupported format {format}")));
+                }
+            }
+        }
+
+        let (height, width) = match reader.line()?.split_whitespace().collect::<Vec<_>>()[..] {
+            ["-Y", height, "+X", width] => {
+                (height.parse::<usize>().ok(), width.parse::<usize>().ok())
+            }
+            _ => (None, None),
+        };
+        let (Some(height), Some(width)) = (height, width) else {
+            return Err(error("expected a -Y <height> +X <width> resolution"));
+        };
+        if width == 0 || height == 0 {
+            return Err(error("image has no pixels"));
+        }
+
+        // check the size against the data before allocating for it: a scanline takes at
+        // least 4 bytes per pixel flat, or 8 bytes per 127 pixels run-length encoded
+        let min_scanline = if (8..=0x7fff).contains(&width) {
+            4 + 8 * width.div_ceil(127)
+        } else {
+            width
+                .checked_mul(4)
+                .ok_or_else(|| error("image is too wide"))?
+        };
+        let fits = width.checked_mul(height).is_some()
+            && min_scanline
+                .checked_mul(height)
+                .is_some_and(|size| size <= bytes.len() - reader.position);
+        if !fits {
+            return Err(error("image is larger than its pixel data"));
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        for y in 0..height {
+            for (x, pixel) in reader.scanline(width)?.into_iter().enumerate() {
+                canvas.write_pixel(x, y, rgbe_to_color(pixel));
+            }
+        }
+        Ok(canvas)
+    }
+
+    pub fn from_hdr_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Self::from_hdr(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Canvas, Color};
+
+    fn header(width: usize, height: usize) -> Vec<u8> {
+        format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\nEXPOSURE=1.0\n\n-Y {height} +X {width}\n")
+            .into_bytes()
+    }
+
+    #[test]
+    fn reading_a_file_with_wrong_magic_number() {
+        let mut hdr = header(1, 1);
+        hdr[1] = b'!';
+        assert!(Canvas::from_hdr(&hdr).is_err());
+    }
+
+    #[test]
+    fn rejects_xyz_images() {
+        let hdr = b"#?RADIANCE\nFORMAT=32-bit_rle_xyze\n\n-Y 1 +X 1\n\0\0\0\0";
+        assert!(Canvas::from_hdr(hdr).is_err());
+    }
+
+    #[test]
+    fn reading_flat_rgbe_pixels() {
+        let mut hdr = header(2, 2);
+        hdr.extend([
+            128, 64, 0, 129, 0, 0, 0, 0, 128, 128, 128, 128, 128, 0, 0, 136,
+        ]);
+        let c = Canvas::from_hdr(&hdr).unwrap();
+        assert_eq!(c.width(), 2);
+        assert_eq!(c.height(), 2);
+        assert_eq!(c.pixel_at(0, 0), Color::new(1., 0.5, 0.));
+        assert_eq!(c.pixel_at(1, 0), Color::new(0., 0., 0.));
+        assert_eq!(c.pixel_at(0, 1), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(c.pixel_at(1, 1), Color::new(128., 0., 0.));
+    }
+
+    #[test]
+    fn reading_run_length_encoded_scanlines() {
+        let mut hdr = header(8, 1);
+        hdr.extend([2, 2, 0, 8]);
+        // red: a run of eight
+        hdr.extend([136, 128]);
+        // green: four literals then a run of four
+        hdr.extend([4, 0, 64, 128, 192, 132, 0]);
+        // blue: a run of eight zeros
+        hdr.extend([136, 0]);
+        // exponent
+        hdr.extend([136, 129]);
+        let c = Canvas::from_hdr(&hdr).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Color::new(1., 0., 0.));
+        assert_eq!(c.pixel_at(2, 0), Color::new(1., 1., 0.));
+        assert_eq!(c.pixel_at(7, 0), Color::new(1., 0., 0.));
+    }
+
+    #[test]
+    fn truncated_hdr_is_an_error() {
+        let mut hdr = header(2, 2);
+        hdr.extend([128, 128, 128, 128]);
+        assert!(Canvas::from_hdr(&hdr).is_err());
+
+        let mut hdr = header(8, 1);
+        hdr.extend([2, 2, 0, 8, 136, 128]);
+        assert!(Canvas::from_hdr(&hdr).is_err());
+    }
+
+    #[test]
+    fn oversized_dimensions_are_an_error() {
+        let mut hdr = header(2, usize::MAX);
+        hdr.extend([128, 128, 128, 128]);
+        assert!(Canvas::from_hdr(&hdr).is_err());
+
+        let mut hdr = header(100_000, 100_000);
+        hdr.extend([2, 2, 0, 8, 136, 128]);
+        assert!(Canvas::from_hdr(&hdr).is_err());
+    }
+
+    #[test]
+    fn empty_images_are_an_error() {
+        assert!(Canvas::from_hdr(&header(0, 0)).is_err());
+        assert!(Canvas::from_hdr(&header(4, 0)).is_err());
+    }
+}
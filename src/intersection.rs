@@ -1,16 +1,138 @@
-use crate::sphere::Sphere;
+use crate::{ray::Ray, shape::Shape, tuple::Tuple, EPSILON};
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Intersection {
+pub struct Intersection<'a> {
     pub t: f64,
-    pub object: Sphere,
+    pub object: &'a dyn Shape,
+    /// Barycentric coordinates of the hit, for shapes that have them.
+    pub uv: Option<(f64, f64)>,
+}
+
+/// Values precomputed at a hit so shading doesn't have to recompute them.
+#[derive(Clone, Copy, Debug)]
+pub struct Computations<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+    pub point: Tuple,
+    /// `point` nudged along the normal so shadow rays don't hit the surface they start on.
+    pub over_point: Tuple,
+    pub eyev: Tuple,
+    pub normalv: Tuple,
+    /// `point` nudged below the surface, where refracted rays start.
+    pub under_point: Tuple,
+    /// The incoming ray bounced off the surface.
+    pub reflectv: Tuple,
+    pub inside: bool,
+    /// Refractive index of the material the ray is leaving.
+    pub n1: f64,
+    /// Refractive index of the material the ray is entering.
+    pub n2: f64,
+}
+
+impl<'a> Intersection<'a> {
+    pub fn new(t: f64, object: &'a dyn Shape) -> Self {
+        Self {
+            t,
+            object,
+            uv: None,
+        }
+    }
+
+    pub fn with_uv(t: f64, object: &'a dyn Shape, u: f64, v: f64) -> Self {
+        Self {
+            t,
+            object,
+            uv: Some((u, v)),
+        }
+    }
+
+    /// `xs` is every intersection along `ray`, sorted, including this one; it tells which
+    /// objects the ray is inside of on either side of the hit.
+    pub fn prepare_computations(&self, ray: Ray, xs: &Intersections<'a>) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let mut normalv = self.object.normal_at_hit(point, self);
+        let inside = normalv.dot(eyev) < 0.;
+
+        if inside {
+            normalv = -normalv;
+        }
+
+        let (n1, n2) = self.refractive_indices(xs);
+
+        Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            over_point: point + normalv * EPSILON,
+            // just below the surface, where refracted rays start
+            under_point: point + normalv * -EPSILON,
+            eyev,
+            normalv,
+            reflectv: ray.direction.reflect(normalv),
+            inside,
+            n1,
+            n2,
+        }
+    }
+
+    /// Walks `xs` keeping track of the objects the ray is inside of, up to this hit.
+    fn refractive_indices(&self, xs: &Intersections<'a>) -> (f64, f64) {
+        let mut containers: Vec<&dyn Shape> = vec![];
+        let index_of = |containers: &[&dyn Shape]| {
+            containers
+                .last()
+                .map_or(1., |object| object.material().refractive_index)
+        };
+
+        for i in &xs.0 {
+            let n1 = index_of(&containers);
+
+            match containers.iter().position(|object| *object == i.object) {
+                Some(position) => {
+                    containers.remove(position);
+                }
+                None => containers.push(i.object),
+            }
+
+            if i == self {
+                return (n1, index_of(&containers));
+            }
+        }
+
+        (1., 1.)
+    }
+}
+
+impl Computations<'_> {
+    /// The Schlick approximation of the Fresnel effect: the fraction of light reflected
+    /// rather than refracted at this hit.
+    pub fn schlick(&self) -> f64 {
+        let mut cos = self.eyev.dot(self.normalv);
+
+        if self.n1 > self.n2 {
+            let n = self.n1 / self.n2;
+            let sin2_t = n * n * (1. - cos * cos);
+            if sin2_t > 1. {
+                return 1.;
+            }
+            cos = (1. - sin2_t).sqrt();
+        }
+
+        let r0 = ((self.n1 - self.n2) / (self.n1 + self.n2)).powi(2);
+        r0 + (1. - r0) * (1. - cos).powi(5)
+    }
 }
 
 #[derive(Clone, Debug)]
-pub struct Intersections(pub Vec<Intersection>);
+pub struct Intersections<'a>(pub Vec<Intersection<'a>>);
+
+impl<'a> Intersections<'a> {
+    pub fn sort(&mut self) {
+        self.0.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+    }
 
-impl Intersections {
-    pub fn hit(&self) -> Option<&Intersection> {
+    pub fn hit(&self) -> Option<&Intersection<'a>> {
         self.0
             .iter()
             .filter(|i| i.t >= 0.)
@@ -20,16 +142,29 @@ impl Intersections {
 
 #[cfg(test)]
 mod tests {
-    use crate::{point, ray::Ray, sphere::Sphere, tuple::Tuple, vector};
+    use std::f64::consts::{FRAC_1_SQRT_2, SQRT_2};
+
+    use crate::{
+        equal, material::Material, matrix::Matrix4, plane::Plane, point, ray::Ray, shape::Shape,
+        sphere::Sphere, tuple::Tuple, vector, EPSILON,
+    };
 
     use super::{Intersection, Intersections};
 
+    #[test]
+    fn intersection_can_encapsulate_u_and_v() {
+        let s = Sphere::default();
+        let i = Intersection::with_uv(3.5, &s, 0.2, 0.4);
+        assert_eq!(i.uv, Some((0.2, 0.4)));
+        assert_eq!(Intersection::new(3.5, &s).uv, None);
+    }
+
     #[test]
     fn aggregate_intersection() {
         let s = Sphere::default();
-        let i1 = Intersection { t: 1., object: s };
+        let i1 = Intersection::new(1., &s);
 
-        let i2 = Intersection { t: 2., object: s };
+        let i2 = Intersection::new(2., &s);
 
         let xs = Intersections(vec![i1, i2]);
 
@@ -47,15 +182,15 @@ mod tests {
         let s = Sphere::default();
         let xs = s.intersect(r);
         assert_eq!(xs.0.len(), 2);
-        assert_eq!(xs.0[0].object, Sphere::default());
-        assert_eq!(xs.0[1].object, Sphere::default());
+        assert_eq!(xs.0[0].object, &s as &dyn Shape);
+        assert_eq!(xs.0[1].object, &s as &dyn Shape);
     }
 
     #[test]
     fn hit_positive_t() {
         let s = Sphere::default();
-        let i1 = Intersection { t: 1., object: s };
-        let i2 = Intersection { t: 2., object: s };
+        let i1 = Intersection::new(1., &s);
+        let i2 = Intersection::new(2., &s);
         let xs = Intersections(vec![i1, i2]);
         assert_eq!(xs.hit(), Some(&i1));
     }
@@ -63,8 +198,8 @@ mod tests {
     #[test]
     fn hit_negative_t() {
         let s = Sphere::default();
-        let i1 = Intersection { t: -1., object: s };
-        let i2 = Intersection { t: 1., object: s };
+        let i1 = Intersection::new(-1., &s);
+        let i2 = Intersection::new(1., &s);
         let xs = Intersections(vec![i2, i1]);
         assert_eq!(xs.hit(), Some(&i2));
     }
@@ -72,8 +207,8 @@ mod tests {
     #[test]
     fn hit_intersections_negative_t() {
         let s = Sphere::default();
-        let i1 = Intersection { t: -2., object: s };
-        let i2 = Intersection { t: -1., object: s };
+        let i1 = Intersection::new(-2., &s);
+        let i2 = Intersection::new(-1., &s);
         let xs = Intersections(vec![i2, i1]);
         assert_eq!(xs.hit(), None);
     }
@@ -81,11 +216,180 @@ mod tests {
     #[test]
     fn hit_lowest_intersection() {
         let s = Sphere::default();
-        let i1 = Intersection { t: 5., object: s };
-        let i2 = Intersection { t: 7., object: s };
-        let i3 = Intersection { t: -3., object: s };
-        let i4 = Intersection { t: 2., object: s };
+        let i1 = Intersection::new(5., &s);
+        let i2 = Intersection::new(7., &s);
+        let i3 = Intersection::new(-3., &s);
+        let i4 = Intersection::new(2., &s);
         let xs = Intersections(vec![i1, i2, i3, i4]);
         assert_eq!(xs.hit(), Some(&i4));
     }
+
+    #[test]
+    fn precomputing_state_of_intersection() {
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let shape = Sphere::default();
+        let i = Intersection::new(4., &shape);
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        assert_eq!(comps.t, i.t);
+        assert_eq!(comps.object, i.object);
+        assert_eq!(comps.point, point!(0., 0., -1.));
+        assert_eq!(comps.eyev, vector!(0., 0., -1.));
+        assert_eq!(comps.normalv, vector!(0., 0., -1.));
+    }
+
+    #[test]
+    fn hit_when_intersection_occurs_on_the_outside() {
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let shape = Sphere::default();
+        let i = Intersection::new(4., &shape);
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        assert!(!comps.inside);
+    }
+
+    #[test]
+    fn hit_when_intersection_occurs_on_the_inside() {
+        let r = Ray {
+            origin: point!(0., 0., 0.),
+            direction: vector!(0., 0., 1.),
+        };
+        let shape = Sphere::default();
+        let i = Intersection::new(1., &shape);
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        assert_eq!(comps.point, point!(0., 0., 1.));
+        assert_eq!(comps.eyev, vector!(0., 0., -1.));
+        assert!(comps.inside);
+        assert_eq!(comps.normalv, vector!(0., 0., -1.));
+    }
+
+    #[test]
+    fn precomputing_the_reflection_vector() {
+        let shape = Plane::default();
+        let r = Ray {
+            origin: point!(0., 1., -1.),
+            direction: vector!(0., -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        };
+        let i = Intersection::new(SQRT_2, &shape);
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        assert_eq!(comps.reflectv, vector!(0., FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn hit_should_offset_the_point() {
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let mut shape = Sphere::default();
+        shape.set_transform(Matrix4::translate(0., 0., 1.));
+        let i = Intersection::new(5., &shape);
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        assert!(comps.over_point.z < -EPSILON / 2.);
+        assert!(comps.point.z > comps.over_point.z);
+    }
+
+    fn glass_sphere() -> Sphere {
+        let mut s = Sphere::default();
+        s.material = Material::glass();
+        s
+    }
+
+    #[test]
+    fn finding_n1_and_n2_at_various_intersections() {
+        let mut a = glass_sphere();
+        a.set_transform(Matrix4::scaling(2., 2., 2.));
+        a.material.refractive_index = 1.5;
+        let mut b = glass_sphere();
+        b.set_transform(Matrix4::translate(0., 0., -0.25));
+        b.material.refractive_index = 2.;
+        let mut c = glass_sphere();
+        c.set_transform(Matrix4::translate(0., 0., 0.25));
+        c.material.refractive_index = 2.5;
+        let r = Ray {
+            origin: point!(0., 0., -4.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = Intersections(vec![
+            Intersection::new(2., &a),
+            Intersection::new(2.75, &b),
+            Intersection::new(3.25, &c),
+            Intersection::new(4.75, &b),
+            Intersection::new(5.25, &c),
+            Intersection::new(6., &a),
+        ]);
+        let expected = [
+            (1.0, 1.5),
+            (1.5, 2.0),
+            (2.0, 2.5),
+            (2.5, 2.5),
+            (2.5, 1.5),
+            (1.5, 1.0),
+        ];
+        for (i, (n1, n2)) in xs.0.iter().zip(expected) {
+            let comps = i.prepare_computations(r, &xs);
+            assert_eq!(comps.n1, n1);
+            assert_eq!(comps.n2, n2);
+        }
+    }
+
+    #[test]
+    fn under_point_is_offset_below_the_surface() {
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let mut shape = glass_sphere();
+        shape.set_transform(Matrix4::translate(0., 0., 1.));
+        let i = Intersection::new(5., &shape);
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        assert!(comps.under_point.z > EPSILON / 2.);
+        assert!(comps.point.z < comps.under_point.z);
+    }
+
+    #[test]
+    fn schlick_approximation_under_total_internal_reflection() {
+        let shape = glass_sphere();
+        let r = Ray {
+            origin: point!(0., 0., FRAC_1_SQRT_2),
+            direction: vector!(0., 1., 0.),
+        };
+        let xs = Intersections(vec![
+            Intersection::new(-FRAC_1_SQRT_2, &shape),
+            Intersection::new(FRAC_1_SQRT_2, &shape),
+        ]);
+        let comps = xs.0[1].prepare_computations(r, &xs);
+        assert_eq!(comps.schlick(), 1.);
+    }
+
+    #[test]
+    fn schlick_approximation_with_perpendicular_viewing_angle() {
+        let shape = glass_sphere();
+        let r = Ray {
+            origin: point!(0., 0., 0.),
+            direction: vector!(0., 1., 0.),
+        };
+        let xs = Intersections(vec![
+            Intersection::new(-1., &shape),
+            Intersection::new(1., &shape),
+        ]);
+        let comps = xs.0[1].prepare_computations(r, &xs);
+        assert!(equal(comps.schlick(), 0.04));
+    }
+
+    #[test]
+    fn schlick_approximation_with_small_angle_and_n2_greater_than_n1() {
+        let shape = glass_sphere();
+        let r = Ray {
+            origin: point!(0., 0.99, -2.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = Intersections(vec![Intersection::new(1.8589, &shape)]);
+        let comps = xs.0[0].prepare_computations(r, &xs);
+        assert!(equal(comps.schlick(), 0.48873));
+    }
 }
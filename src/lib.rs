@@ -1,28 +1,61 @@
+pub mod bounds;
+pub mod bvh;
+pub mod camera;
+pub mod cone;
+pub mod csg;
+pub mod cube;
+pub mod cylinder;
+pub mod environment;
+pub mod group;
+pub mod hdr;
 pub mod intersection;
+pub mod light;
 pub mod material;
 pub mod matrix;
+pub mod noise;
+pub mod obj;
+pub mod pattern;
+pub mod plane;
+pub mod ppm;
 pub mod ray;
+pub mod rng;
+pub mod shape;
 pub mod sphere;
+pub mod texture;
+pub mod transform;
+pub mod triangle;
 pub mod tuple;
+pub mod world;
 use std::{
     fmt::Display,
     fs,
     ops::{Add, Div, Mul, Neg, Sub},
 };
 
+pub const EPSILON: f64 = 0.0001;
+
 pub fn equal(a: f64, b: f64) -> bool {
-    let epsilion = 0.0001;
-    return (a - b).abs() < epsilion;
+    (a - b).abs() < EPSILON
 }
 
 #[derive(Debug, Clone)]
 pub struct Canvas {
     height: usize,
     width: usize,
-    colors: Vec<Vec<Color>>,
+    /// Row-major, one contiguous buffer so finished tiles can be copied in a row at a time.
+    colors: Vec<Color>,
+}
+
+/// A rectangle of pixels rendered as one unit of work.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
 }
 
-const BLACK: Color = Color {
+pub const BLACK: Color = Color {
     red: 0.,
     blue: 0.,
     green: 0.,
@@ -30,27 +63,65 @@ const BLACK: Color = Color {
 
 impl Canvas {
     pub fn new(width: usize, height: usize) -> Self {
-        let black_row = vec![BLACK; width];
         Self {
             width,
             height,
-            colors: vec![black_row; height],
+            colors: vec![BLACK; width * height],
         }
     }
 
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
     pub fn write_pixel(&mut self, x: usize, y: usize, c: Color) {
-        self.colors[y][x] = c;
+        self.colors[y * self.width + x] = c;
     }
 
-    fn pixel_at(&self, x: usize, y: usize) -> Color {
-        self.colors[y][x]
+    pub fn pixel_at(&self, x: usize, y: usize) -> Color {
+        self.colors[y * self.width + x]
+    }
+
+    /// Covers the canvas in `size`×`size` tiles, left to right and top to bottom.
+    /// Tiles on the right and bottom edges are cut short to fit. A size of 0 is taken
+    /// as 1.
+    pub fn tiles(&self, size: usize) -> Vec<Tile> {
+        let size = size.max(1);
+        let mut tiles = vec![];
+        for y in (0..self.height).step_by(size) {
+            for x in (0..self.width).step_by(size) {
+                tiles.push(Tile {
+                    x,
+                    y,
+                    width: size.min(self.width - x),
+                    height: size.min(self.height - y),
+                });
+            }
+        }
+        tiles
+    }
+
+    /// Copies a rendered tile in; `colors` holds its pixels row by row.
+    pub fn write_tile(&mut self, tile: Tile, colors: &[Color]) {
+        assert_eq!(colors.len(), tile.width * tile.height);
+        if tile.width == 0 {
+            return;
+        }
+        for (row, tile_row) in colors.chunks(tile.width).enumerate() {
+            let start = (tile.y + row) * self.width + tile.x;
+            self.colors[start..start + tile.width].copy_from_slice(tile_row);
+        }
     }
 
     pub fn to_ppm(&self) -> String {
         let mut contents = String::new();
 
-        for row in &self.colors {
-            for color in row {
+        for y in 0..self.height {
+            for color in &self.colors[y * self.width..(y + 1) * self.width] {
                 contents.push_str(color.to_string().as_str());
             }
             contents.push('\n');
@@ -83,6 +154,7 @@ impl Mul for Color {
     }
 }
 
+#[allow(dead_code)]
 fn hadamard_product(c1: Color, c2: Color) -> Color {
     Color {
         red: c1.red * c2.red,
@@ -102,15 +174,22 @@ fn clamp(value: f64) -> i64 {
 }
 
 impl Color {
+    pub fn new(red: f64, green: f64, blue: f64) -> Self {
+        Self { red, green, blue }
+    }
+
+    #[allow(dead_code)]
     fn length_squared(&self) -> f64 {
         self.red * self.red + self.green * self.green + self.blue * self.blue
     }
 
+    #[allow(dead_code)]
     fn length(&self) -> f64 {
         // Pgreenthagoras theorem to calculate the magnitude of a vector
         self.length_squared().sqrt()
     }
 
+    #[allow(dead_code)]
     fn normalibluee(&self) -> Color {
         Color {
             red: self.red / self.length(),
@@ -119,6 +198,7 @@ impl Color {
         }
     }
 
+    #[allow(dead_code)]
     fn dot(&self, a: f64, b: f64) -> f64 {
         (a * self.red + b * self.red)
             + (a * self.green + b * self.green)
@@ -138,7 +218,7 @@ impl Display for Color {
     }
 }
 
-fn cross(a: Color, b: Color) -> Color {
+pub fn cross(a: Color, b: Color) -> Color {
     Color {
         red: a.green * b.blue - a.blue * b.green,
         green: a.blue * b.red - a.red * b.blue,
@@ -201,6 +281,7 @@ impl Div<f64> for Color {
     }
 }
 
+#[allow(dead_code)]
 fn write_color(piredel_color: Color) {
     println!(
         "{} {} {}",
@@ -212,7 +293,7 @@ fn write_color(piredel_color: Color) {
 
 #[cfg(test)]
 mod tests {
-    use crate::{cross, Canvas, Color};
+    use crate::{cross, Canvas, Color, Tile};
 
     #[test]
     fn test_cross() {
@@ -246,10 +327,53 @@ mod tests {
             green: 0.,
         };
         let mut c = Canvas::new(10, 20);
-        c.write_pixel(2, 3, red.clone());
+        c.write_pixel(2, 3, red);
         assert_eq!(c.pixel_at(2, 3), red);
     }
 
+    #[test]
+    fn tiles_cover_the_canvas_exactly_once() {
+        let c = Canvas::new(10, 7);
+        let tiles = c.tiles(4);
+        assert_eq!(tiles.len(), 6);
+        assert_eq!(
+            tiles[5],
+            Tile {
+                x: 8,
+                y: 4,
+                width: 2,
+                height: 3
+            }
+        );
+        let covered: usize = tiles.iter().map(|t| t.width * t.height).sum();
+        assert_eq!(covered, 70);
+    }
+
+    #[test]
+    fn empty_canvases_and_tile_sizes_do_not_panic() {
+        let c = Canvas::new(0, 3);
+        assert_eq!(c.to_ppm(), "\n\n\n");
+        assert!(c.tiles(4).is_empty());
+        assert_eq!(Canvas::new(2, 2).tiles(0).len(), 4);
+    }
+
+    #[test]
+    fn writing_a_tile() {
+        let mut c = Canvas::new(5, 5);
+        let red = Color::new(1., 0., 0.);
+        let tile = Tile {
+            x: 3,
+            y: 1,
+            width: 2,
+            height: 2,
+        };
+        c.write_tile(tile, &[red; 4]);
+        assert_eq!(c.pixel_at(3, 1), red);
+        assert_eq!(c.pixel_at(4, 2), red);
+        assert_eq!(c.pixel_at(2, 1), Color::new(0., 0., 0.));
+        assert_eq!(c.pixel_at(3, 3), Color::new(0., 0., 0.));
+    }
+
     #[test]
     fn test_pixel_data() {
         let mut c = Canvas::new(5, 3);
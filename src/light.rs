@@ -0,0 +1,650 @@
+use std::{f64::consts::PI, fmt::Debug};
+
+use crate::{rng::Rng, tuple::Tuple, vector, Color};
+
+/// Light arriving at a shaded point from one place on a light.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LightSample {
+    /// Unit vector from the shaded point toward the light.
+    pub direction: Tuple,
+    /// How far the light is along `direction`; infinite for lights with no position.
+    pub distance: f64,
+    /// What reaches the point, after any falloff.
+    pub intensity: Color,
+}
+
+impl LightSample {
+    /// Light from `position`, arriving at `point` undimmed.
+    pub fn toward(point: Tuple, position: Tuple, intensity: Color) -> Self {
+        let v = position - point;
+        Self {
+            direction: v.normalize(),
+            distance: v.magnitude(),
+            intensity,
+        }
+    }
+
+    /// Where a shadow ray cast from `point` stops, or `None` if it never does.
+    pub fn endpoint(&self, point: Tuple) -> Option<Tuple> {
+        self.distance
+            .is_finite()
+            .then(|| point + self.direction * self.distance)
+    }
+}
+
+/// A generator seeded from `point`, so lights sampled at a point come out the same
+/// however a render's pixels are split between threads.
+pub(crate) fn rng_at(point: Tuple) -> Rng {
+    let seed = [point.x, point.y, point.z]
+        .iter()
+        .fold(0, |seed: u64, c| seed.rotate_left(21) ^ c.to_bits());
+    Rng::new(seed)
+}
+
+/// The smallest divisor [`Attenuation::factor`] uses, so a point right at a light gets a
+/// very bright but finite light instead of infinity.
+const MIN_DIVISOR: f64 = 1e-6;
+
+/// How a light dims with distance `d`: its intensity is divided by
+/// `constant + linear·d + quadratic·d²`. All zeros is taken as no falloff at all.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Attenuation {
+    pub constant: f64,
+    pub linear: f64,
+    pub quadratic: f64,
+}
+
+impl Attenuation {
+    /// Full strength at any distance.
+    pub const NONE: Self = Self {
+        constant: 1.,
+        linear: 0.,
+        quadratic: 0.,
+    };
+
+    /// Physical falloff, with distances measured in meters. `meters_per_unit` is how
+    /// long one scene unit is.
+    pub fn inverse_square(meters_per_unit: f64) -> Self {
+        Self {
+            constant: 0.,
+            linear: 0.,
+            quadratic: meters_per_unit * meters_per_unit,
+        }
+    }
+
+    /// What fraction of a light's intensity is left `distance` away.
+    pub fn factor(&self, distance: f64) -> f64 {
+        if self.constant == 0. && self.linear == 0. && self.quadratic == 0. {
+            return 1.;
+        }
+        let divisor = self.constant + (self.linear + self.quadratic * distance) * distance;
+        1. / divisor.max(MIN_DIVISOR)
+    }
+}
+
+impl Default for Attenuation {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+/// A light's total output in physical units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Power {
+    Watts(f64),
+    /// Luminous flux, converted to watts at the peak efficacy of 683 lm/W.
+    Lumens(f64),
+}
+
+impl Power {
+    pub const LUMENS_PER_WATT: f64 = 683.;
+
+    pub fn watts(self) -> f64 {
+        match self {
+            Power::Watts(watts) => watts,
+            Power::Lumens(lumens) => lumens / Self::LUMENS_PER_WATT,
+        }
+    }
+
+    /// Watts per steradian when the power is spread evenly over `solid_angle`.
+    pub fn per_steradian(self, solid_angle: f64) -> f64 {
+        self.watts() / solid_angle
+    }
+}
+
+/// Anything that lights a scene.
+pub trait Light: Debug + Send + Sync {
+    /// The light's color at full strength, which sets the ambient term.
+    fn intensity(&self) -> Color;
+
+    /// Where light reaches `point` from. Lights with size return several samples, which
+    /// are averaged.
+    fn samples(&self, point: Tuple) -> Vec<LightSample>;
+}
+
+/// Light from a single point, equally bright in every direction.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct PointLight {
+    pub position: Tuple,
+    pub intensity: Color,
+    pub attenuation: Attenuation,
+}
+
+impl PointLight {
+    pub fn new(position: Tuple, intensity: Color) -> Self {
+        Self {
+            position,
+            intensity,
+            attenuation: Attenuation::NONE,
+        }
+    }
+
+    /// A bulb giving off `power` of `color` in every direction, falling off with the
+    /// square of the distance. See [`Attenuation::inverse_square`] for `meters_per_unit`.
+    pub fn physical(position: Tuple, color: Color, power: Power, meters_per_unit: f64) -> Self {
+        Self {
+            position,
+            intensity: color * power.per_steradian(4. * PI),
+            attenuation: Attenuation::inverse_square(meters_per_unit),
+        }
+    }
+}
+
+impl Light for PointLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, point: Tuple) -> Vec<LightSample> {
+        let mut sample = LightSample::toward(point, self.position, self.intensity);
+        sample.intensity = self.intensity * self.attenuation.factor(sample.distance);
+        vec![sample]
+    }
+}
+
+/// A point light limited to a cone. Full strength inside `inner_angle` of `direction`,
+/// fading smoothly to nothing at `outer_angle`.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct SpotLight {
+    pub position: Tuple,
+    pub direction: Tuple,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub intensity: Color,
+    pub attenuation: Attenuation,
+}
+
+impl SpotLight {
+    /// A spot at `position` shining toward `target`. Angles are in radians from the axis.
+    pub fn new(
+        position: Tuple,
+        target: Tuple,
+        inner_angle: f64,
+        outer_angle: f64,
+        intensity: Color,
+    ) -> Self {
+        Self {
+            position,
+            direction: (target - position).normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+            attenuation: Attenuation::NONE,
+        }
+    }
+
+    /// A spot giving off `power` of `color` within its outer cone, falling off with the
+    /// square of the distance. See [`Attenuation::inverse_square`] for `meters_per_unit`.
+    /// Panics if `outer_angle` isn't positive, since there'd be no cone to spread the
+    /// power over.
+    pub fn physical(
+        position: Tuple,
+        target: Tuple,
+        inner_angle: f64,
+        outer_angle: f64,
+        color: Color,
+        power: Power,
+        meters_per_unit: f64,
+    ) -> Self {
+        assert!(outer_angle > 0., "a physical spot light needs a cone");
+        let cone = 2. * PI * (1. - outer_angle.cos());
+        Self {
+            intensity: color * power.per_steradian(cone),
+            attenuation: Attenuation::inverse_square(meters_per_unit),
+            ..Self::new(position, target, inner_angle, outer_angle, color)
+        }
+    }
+
+    /// How much of the light reaches along `lightv` (from the light outward): 1 inside
+    /// the inner cone, 0 outside the outer one.
+    pub fn falloff(&self, lightv: Tuple) -> f64 {
+        let cos_angle = lightv.dot(self.direction);
+        let (cos_inner, cos_outer) = (self.inner_angle.cos(), self.outer_angle.cos());
+        if cos_angle >= cos_inner {
+            1.
+        } else if cos_angle <= cos_outer {
+            0.
+        } else {
+            let t = (cos_angle - cos_outer) / (cos_inner - cos_outer);
+            t * t * (3. - 2. * t)
+        }
+    }
+}
+
+impl Light for SpotLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, point: Tuple) -> Vec<LightSample> {
+        let mut sample = LightSample::toward(point, self.position, self.intensity);
+        sample.intensity = self.intensity
+            * self.falloff(-sample.direction)
+            * self.attenuation.factor(sample.distance);
+        vec![sample]
+    }
+}
+
+/// Light from infinitely far away, like the sun: parallel rays and no falloff.
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub struct DirectionalLight {
+    /// The way the light travels.
+    pub direction: Tuple,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Tuple, intensity: Color) -> Self {
+        Self {
+            direction: direction.normalize(),
+            intensity,
+        }
+    }
+}
+
+impl Light for DirectionalLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, _point: Tuple) -> Vec<LightSample> {
+        vec![LightSample {
+            direction: -self.direction,
+            distance: f64::INFINITY,
+            intensity: self.intensity,
+        }]
+    }
+}
+
+/// The surface an area light emits from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AreaShape {
+    /// The parallelogram spanned by `uvec` and `vvec` from `corner`.
+    Rectangle {
+        corner: Tuple,
+        uvec: Tuple,
+        vvec: Tuple,
+    },
+    /// A sphere's surface, divided by height (`u`) and angle around the y axis (`v`).
+    Sphere { center: Tuple, radius: f64 },
+}
+
+/// A light with size, which casts soft-edged shadows. It's sampled on a
+/// `usteps`×`vsteps` grid of cells. Each sample carries the full intensity, and shading
+/// averages them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AreaLight {
+    pub shape: AreaShape,
+    pub usteps: usize,
+    pub vsteps: usize,
+    pub intensity: Color,
+    /// Applied to each sample by its own distance.
+    pub attenuation: Attenuation,
+    /// Picks a random spot inside each cell rather than its center, trading banded
+    /// penumbrae for noise.
+    pub jitter: bool,
+}
+
+impl AreaLight {
+    pub fn rectangle(
+        corner: Tuple,
+        uvec: Tuple,
+        usteps: usize,
+        vvec: Tuple,
+        vsteps: usize,
+        intensity: Color,
+    ) -> Self {
+        Self {
+            shape: AreaShape::Rectangle { corner, uvec, vvec },
+            usteps,
+            vsteps,
+            intensity,
+            attenuation: Attenuation::NONE,
+            jitter: true,
+        }
+    }
+
+    /// A spherical light sampled on a `steps`×`steps` grid.
+    pub fn sphere(center: Tuple, radius: f64, steps: usize, intensity: Color) -> Self {
+        Self {
+            shape: AreaShape::Sphere { center, radius },
+            usteps: steps,
+            vsteps: steps,
+            intensity,
+            attenuation: Attenuation::NONE,
+            jitter: true,
+        }
+    }
+
+    pub fn sample_count(&self) -> usize {
+        self.usteps * self.vsteps
+    }
+
+    /// The point at `(u, v)` on the light, each in `0.0..1.0`.
+    fn point_on_light(&self, u: f64, v: f64) -> Tuple {
+        match self.shape {
+            AreaShape::Rectangle { corner, uvec, vvec } => corner + uvec * u + vvec * v,
+            AreaShape::Sphere { center, radius } => {
+                // equal steps in height cover equal areas of a sphere
+                let y = 1. - 2. * u;
+                let ring = (1. - y * y).sqrt();
+                let angle = 2. * PI * v;
+                center + vector!(ring * angle.cos(), y, ring * angle.sin()) * radius
+            }
+        }
+    }
+
+    /// One position per cell, used to light `point`. The jitter is seeded from `point`.
+    pub fn sample_points(&self, point: Tuple) -> Vec<Tuple> {
+        let mut rng = rng_at(point);
+
+        let mut points = Vec::with_capacity(self.sample_count());
+        for v in 0..self.vsteps {
+            for u in 0..self.usteps {
+                let (du, dv) = if self.jitter {
+                    (rng.next_f64(), rng.next_f64())
+                } else {
+                    (0.5, 0.5)
+                };
+                points.push(self.point_on_light(
+                    (u as f64 + du) / self.usteps as f64,
+                    (v as f64 + dv) / self.vsteps as f64,
+                ));
+            }
+        }
+        points
+    }
+}
+
+impl Light for AreaLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, point: Tuple) -> Vec<LightSample> {
+        self.sample_points(point)
+            .into_iter()
+            .map(|position| {
+                let mut sample = LightSample::toward(point, position, self.intensity);
+                sample.intensity = self.intensity * self.attenuation.factor(sample.distance);
+                sample
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{equal, point, test_color, test_point, tuple::Tuple, vector, Color};
+
+    use std::f64::consts::PI;
+
+    use super::{AreaLight, Attenuation, DirectionalLight, Light, PointLight, Power, SpotLight};
+
+    fn white() -> Color {
+        Color::new(1., 1., 1.)
+    }
+
+    #[test]
+    fn creating_an_area_light() {
+        let light = AreaLight::rectangle(
+            point!(0., 0., 0.),
+            vector!(2., 0., 0.),
+            4,
+            vector!(0., 0., 1.),
+            2,
+            white(),
+        );
+        assert_eq!(light.usteps, 4);
+        assert_eq!(light.vsteps, 2);
+        assert_eq!(light.sample_count(), 8);
+        assert!(light.jitter);
+    }
+
+    #[test]
+    fn finding_cell_centers_on_an_area_light() {
+        let mut light = AreaLight::rectangle(
+            point!(0., 0., 0.),
+            vector!(2., 0., 0.),
+            4,
+            vector!(0., 0., 1.),
+            2,
+            white(),
+        );
+        light.jitter = false;
+        let points = light.sample_points(point!(0., 0., 0.));
+        let cases = [
+            (0, 0, point!(0.25, 0., 0.25)),
+            (1, 0, point!(0.75, 0., 0.25)),
+            (0, 1, point!(0.25, 0., 0.75)),
+            (2, 0, point!(1.25, 0., 0.25)),
+            (3, 1, point!(1.75, 0., 0.75)),
+        ];
+        for (u, v, expected) in cases {
+            test_point!(points[v * 4 + u], expected);
+        }
+    }
+
+    #[test]
+    fn jittered_samples_stay_in_their_cells() {
+        let light = AreaLight::rectangle(
+            point!(0., 0., 0.),
+            vector!(2., 0., 0.),
+            4,
+            vector!(0., 0., 1.),
+            2,
+            white(),
+        );
+        let points = light.sample_points(point!(1., 2., 3.));
+        for v in 0..2 {
+            for u in 0..4 {
+                let p = points[v * 4 + u];
+                assert!((u as f64 * 0.5..(u + 1) as f64 * 0.5).contains(&p.x));
+                assert!((v as f64 * 0.5..(v + 1) as f64 * 0.5).contains(&p.z));
+            }
+        }
+        assert_eq!(points, light.sample_points(point!(1., 2., 3.)));
+        assert_ne!(points, light.sample_points(point!(1., 2., 4.)));
+    }
+
+    #[test]
+    fn sphere_light_samples_lie_on_its_surface() {
+        let light = AreaLight::sphere(point!(1., 2., 3.), 0.5, 4, white());
+        let points = light.sample_points(point!(0., 0., 0.));
+        assert_eq!(points.len(), 16);
+        for p in points {
+            assert!(equal((p - point!(1., 2., 3.)).magnitude(), 0.5));
+        }
+    }
+
+    #[test]
+    fn a_point_light_has_a_position_and_intensity() {
+        let light = PointLight::new(point!(0., 0., 0.), white());
+        assert_eq!(light.position, point!(0., 0., 0.));
+        assert_eq!(light.intensity, white());
+    }
+
+    #[test]
+    fn point_light_sample_points_at_the_light() {
+        let light = PointLight::new(point!(0., 3., 4.), white());
+        let samples = light.samples(point!(0., 0., 0.));
+        assert_eq!(samples.len(), 1);
+        test_point!(samples[0].direction, vector!(0., 0.6, 0.8));
+        assert!(equal(samples[0].distance, 5.));
+        assert_eq!(samples[0].intensity, white());
+        test_point!(
+            samples[0].endpoint(point!(0., 0., 0.)).unwrap(),
+            point!(0., 3., 4.)
+        );
+    }
+
+    #[test]
+    fn directional_light_comes_from_infinitely_far_away() {
+        let light = DirectionalLight::new(vector!(0., -2., 0.), white());
+        for p in [point!(0., 0., 0.), point!(100., -5., 3.)] {
+            let sample = light.samples(p)[0];
+            test_point!(sample.direction, vector!(0., 1., 0.));
+            assert_eq!(sample.distance, f64::INFINITY);
+            assert_eq!(sample.endpoint(p), None);
+        }
+    }
+
+    #[test]
+    fn spot_light_fades_between_its_cones() {
+        let light = SpotLight::new(
+            point!(0., 10., 0.),
+            point!(0., 0., 0.),
+            PI / 8.,
+            PI / 4.,
+            white(),
+        );
+        let at = |p| light.samples(p)[0].intensity.red;
+        assert_eq!(at(point!(0., 0., 0.)), 1.);
+        assert_eq!(at(point!(1., 0., 0.)), 1.);
+        let fading = at(point!(7., 0., 0.));
+        assert!(0. < fading && fading < 1.);
+        assert_eq!(at(point!(20., 0., 0.)), 0.);
+        assert_eq!(at(point!(0., 20., 0.)), 0.);
+    }
+
+    #[test]
+    fn area_light_gives_a_sample_per_cell() {
+        let light = AreaLight::sphere(point!(0., 5., 0.), 1., 3, white());
+        let samples = light.samples(point!(0., 0., 0.));
+        assert_eq!(samples.len(), 9);
+        for sample in samples {
+            assert!(sample.distance >= 4. && sample.distance <= 6.);
+            assert_eq!(sample.intensity, white());
+        }
+    }
+
+    #[test]
+    fn lights_do_not_fade_by_default() {
+        let light = PointLight::new(point!(0., 100., 0.), white());
+        assert_eq!(light.attenuation, Attenuation::NONE);
+        assert_eq!(light.samples(point!(0., 0., 0.))[0].intensity, white());
+    }
+
+    #[test]
+    fn attenuation_combines_constant_linear_and_quadratic_terms() {
+        let a = Attenuation {
+            constant: 1.,
+            linear: 0.5,
+            quadratic: 0.25,
+        };
+        assert!(equal(a.factor(0.), 1.));
+        assert!(equal(a.factor(2.), 1. / 3.));
+        let inverse_square = Attenuation::inverse_square(1.);
+        assert!(equal(inverse_square.factor(2.), 0.25));
+        assert!(equal(inverse_square.factor(4.), 1. / 16.));
+    }
+
+    #[test]
+    fn attenuation_stays_finite_at_the_light() {
+        assert!(Attenuation::inverse_square(1.).factor(0.).is_finite());
+    }
+
+    #[test]
+    fn all_zero_attenuation_has_no_falloff() {
+        let zero = Attenuation {
+            constant: 0.,
+            linear: 0.,
+            quadratic: 0.,
+        };
+        assert_eq!(zero.factor(0.), 1.);
+        assert_eq!(zero.factor(3.), 1.);
+    }
+
+    #[test]
+    fn attenuation_dims_each_kind_of_light_by_distance() {
+        let falloff = Attenuation::inverse_square(1.);
+        let mut point_light = PointLight::new(point!(0., 2., 0.), white());
+        point_light.attenuation = falloff;
+        let mut spot = SpotLight::new(point!(0., 2., 0.), point!(0., 0., 0.), 0.5, 1., white());
+        spot.attenuation = falloff;
+        let mut area = AreaLight::sphere(point!(0., 2., 0.), 0.001, 1, white());
+        area.attenuation = falloff;
+
+        let lights: [&dyn Light; 3] = [&point_light, &spot, &area];
+        for light in lights {
+            let sample = light.samples(point!(0., 0., 0.))[0];
+            assert!((sample.intensity.red - 0.25).abs() < 0.001);
+        }
+    }
+
+    #[test]
+    fn converting_light_power_units() {
+        assert_eq!(Power::Watts(60.).watts(), 60.);
+        assert!(equal(Power::Lumens(683.).watts(), 1.));
+        assert!(equal(Power::Watts(4. * PI).per_steradian(4. * PI), 1.));
+    }
+
+    #[test]
+    fn physical_point_light_is_one_watt_per_steradian_at_one_meter() {
+        let light = PointLight::physical(point!(0., 1., 0.), white(), Power::Watts(4. * PI), 1.);
+        let sample = light.samples(point!(0., 0., 0.))[0];
+        assert!(equal(sample.intensity.red, 1.));
+    }
+
+    #[test]
+    fn physical_lights_look_the_same_when_a_scene_is_rescaled() {
+        let power = Power::Lumens(800.);
+        // a room modeled in meters, and the same room modeled in centimeters
+        let meters = PointLight::physical(point!(0., 2.5, 0.), white(), power, 1.);
+        let centimeters = PointLight::physical(point!(0., 250., 0.), white(), power, 0.01);
+        let a = meters.samples(point!(1., 0., 0.))[0].intensity;
+        let b = centimeters.samples(point!(100., 0., 0.))[0].intensity;
+        test_color!(a, b);
+    }
+
+    #[test]
+    fn physical_spot_light_concentrates_power_in_its_cone() {
+        let power = Power::Watts(100.);
+        let bulb = PointLight::physical(point!(0., 1., 0.), white(), power, 1.);
+        let spot = SpotLight::physical(
+            point!(0., 1., 0.),
+            point!(0., 0., 0.),
+            PI / 8.,
+            PI / 4.,
+            white(),
+            power,
+            1.,
+        );
+        let origin = point!(0., 0., 0.);
+        assert!(spot.samples(origin)[0].intensity.red > bulb.samples(origin)[0].intensity.red);
+    }
+
+    #[test]
+    #[should_panic(expected = "needs a cone")]
+    fn physical_spot_light_needs_a_cone() {
+        SpotLight::physical(
+            point!(0., 1., 0.),
+            point!(0., 0., 0.),
+            0.,
+            0.,
+            white(),
+            Power::Watts(100.),
+            1.,
+        );
+    }
+}
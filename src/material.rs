@@ -1,4 +1,13 @@
-use crate::{tuple::Tuple, Color};
+use std::sync::Arc;
+
+use crate::{
+    light::{Light, LightSample},
+    noise::BumpMap,
+    pattern::Pattern,
+    shape::Shape,
+    tuple::Tuple,
+    Color,
+};
 
 const BLACK: Color = Color {
     red: 0.,
@@ -6,56 +15,120 @@ const BLACK: Color = Color {
     green: 0.,
 };
 
-#[derive(PartialEq, Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub struct Material {
     pub color: Color,
+    /// Used instead of `color` when set.
+    pub pattern: Option<Arc<dyn Pattern>>,
     pub ambient: f64,
     pub diffuse: f64,
     pub specular: f64,
     pub shininess: f64,
+    /// How much of a reflected ray's color is mixed in: 0 is matte, 1 is a perfect mirror.
+    pub reflective: f64,
+    /// How much light passes through: 0 is opaque, 1 lets everything through.
+    pub transparency: f64,
+    /// How much light bends entering the material; 1 for vacuum, 1.5 for glass.
+    pub refractive_index: f64,
+    /// Tilts the surface normals to fake small bumps.
+    pub bump: Option<BumpMap>,
 }
 
-pub struct PointLight {
-    position: Tuple,
-    intensity: Color,
-}
+/// Patterns are compared by identity, like shapes.
+impl PartialEq for Material {
+    fn eq(&self, other: &Self) -> bool {
+        let same_pattern = match (&self.pattern, &other.pattern) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
 
-impl PointLight {
-    pub fn new(position: Tuple, intensity: Color) -> Self {
-        Self {
-            position,
-            intensity,
-        }
+        same_pattern
+            && self.color == other.color
+            && self.ambient == other.ambient
+            && self.diffuse == other.diffuse
+            && self.specular == other.specular
+            && self.shininess == other.shininess
+            && self.reflective == other.reflective
+            && self.transparency == other.transparency
+            && self.refractive_index == other.refractive_index
+            && self.bump == other.bump
     }
 }
 
 impl Material {
-    pub fn lightning(&self, light: PointLight, point: Tuple, eyev: Tuple, normalv: Tuple) -> Color {
-        let effective_color = self.color * light.intensity;
-        let lightv = (light.position - point).normalize();
-        let ambient = effective_color * self.ambient;
-
-        let light_dot_normal = lightv.dot(normalv);
-        let mut diffuse = BLACK;
-        let mut specular = BLACK;
-
-        if light_dot_normal < 0. {
-            diffuse = BLACK;
-            specular = BLACK;
-        } else {
-            diffuse = effective_color * self.diffuse * light_dot_normal;
-            let reflectv = -lightv.reflect(normalv);
-            let reflect_dot_eye = reflectv.dot(eyev);
-
-            if reflect_dot_eye <= 0. {
-                specular = BLACK;
-            } else {
-                let factor = reflect_dot_eye.powf(self.shininess);
-                specular = light.intensity * self.specular * factor;
-            }
+    /// A fully transparent material that bends light like glass.
+    pub fn glass() -> Self {
+        Self {
+            transparency: 1.,
+            refractive_index: 1.5,
+            ..Self::default()
         }
+    }
 
-        ambient + diffuse + specular
+    /// Phong shading at `point`. `visibility` is the fraction of the light reaching the
+    /// point: 1.0 when fully lit, 0.0 when in shadow, which leaves only the ambient term.
+    pub fn lightning(
+        &self,
+        object: &dyn Shape,
+        light: &dyn Light,
+        point: Tuple,
+        eyev: Tuple,
+        normalv: Tuple,
+        visibility: f64,
+    ) -> Color {
+        let samples: Vec<_> = light
+            .samples(point)
+            .into_iter()
+            .map(|sample| (sample, visibility))
+            .collect();
+        self.lightning_samples(object, light.intensity(), &samples, point, eyev, normalv)
+    }
+
+    /// Phong shading from a light's samples, each paired with how visible it is from
+    /// `point`. `intensity` sets the ambient term, which is counted once; diffuse and
+    /// specular are averaged across samples.
+    pub fn lightning_samples(
+        &self,
+        object: &dyn Shape,
+        intensity: Color,
+        samples: &[(LightSample, f64)],
+        point: Tuple,
+        eyev: Tuple,
+        normalv: Tuple,
+    ) -> Color {
+        let color = match &self.pattern {
+            Some(pattern) => pattern.pattern_at_shape(object, point),
+            None => self.color,
+        };
+        let ambient = color * intensity * self.ambient;
+
+        let (diffuse, specular) = samples.iter().fold(
+            (BLACK, BLACK),
+            |(diffuse_sum, specular_sum), &(sample, visibility)| {
+                let lightv = sample.direction;
+                let light_dot_normal = lightv.dot(normalv);
+                if light_dot_normal < 0. || visibility == 0. {
+                    return (diffuse_sum, specular_sum);
+                }
+
+                let diffuse = color * sample.intensity * self.diffuse * light_dot_normal;
+                let reflectv = -lightv.reflect(normalv);
+                let reflect_dot_eye = reflectv.dot(eyev);
+                let specular = if reflect_dot_eye <= 0. {
+                    BLACK
+                } else {
+                    sample.intensity * self.specular * reflect_dot_eye.powf(self.shininess)
+                };
+
+                (
+                    diffuse_sum + diffuse * visibility,
+                    specular_sum + specular * visibility,
+                )
+            },
+        );
+
+        let count = samples.len().max(1) as f64;
+        ambient + diffuse / count + specular / count
     }
 }
 
@@ -67,19 +140,48 @@ impl Default for Material {
                 blue: 1.,
                 green: 1.,
             },
+            pattern: None,
             ambient: 0.1,
             diffuse: 0.9,
             specular: 0.9,
             shininess: 200.,
+            reflective: 0.,
+            transparency: 0.,
+            refractive_index: 1.,
+            bump: None,
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{equal, point, test_color, tuple::Tuple, vector, Color};
+    use std::{f64::consts::FRAC_1_SQRT_2, sync::Arc};
+
+    use crate::{
+        equal,
+        light::{AreaLight, Attenuation, PointLight},
+        pattern::Stripe,
+        point,
+        sphere::Sphere,
+        test_color,
+        tuple::Tuple,
+        vector, Color, BLACK,
+    };
 
-    use super::{Material, PointLight};
+    use super::Material;
+
+    #[test]
+    fn reflectivity_for_the_default_material() {
+        let m = Material::default();
+        assert_eq!(m.reflective, 0.);
+    }
+
+    #[test]
+    fn transparency_and_refractive_index_for_the_default_material() {
+        let m = Material::default();
+        assert_eq!(m.transparency, 0.);
+        assert_eq!(m.refractive_index, 1.);
+    }
 
     #[test]
     fn lighting_with_eye_between_light_and_surface() {
@@ -94,9 +196,10 @@ mod tests {
                 blue: 1.,
                 green: 1.,
             },
+            attenuation: Attenuation::NONE,
         };
 
-        let result = m.lightning(light, position, eyev, normalv);
+        let result = m.lightning(&Sphere::default(), &light, position, eyev, normalv, 1.);
         assert_eq!(
             result,
             Color {
@@ -120,9 +223,10 @@ mod tests {
                 blue: 1.,
                 green: 1.,
             },
+            attenuation: Attenuation::NONE,
         };
 
-        let result = m.lightning(light, position, eyev, normalv);
+        let result = m.lightning(&Sphere::default(), &light, position, eyev, normalv, 1.);
         assert_eq!(
             result,
             Color {
@@ -146,8 +250,9 @@ mod tests {
                 blue: 1.,
                 green: 1.,
             },
+            attenuation: Attenuation::NONE,
         };
-        let result = m.lightning(light, position, eyev, normalv);
+        let result = m.lightning(&Sphere::default(), &light, position, eyev, normalv, 1.);
         test_color!(
             result,
             Color {
@@ -173,7 +278,7 @@ mod tests {
             },
         );
 
-        let result = m.lightning(light, position, eyev, normalv);
+        let result = m.lightning(&Sphere::default(), &light, position, eyev, normalv, 1.);
         test_color!(
             result,
             Color {
@@ -199,7 +304,7 @@ mod tests {
             },
         );
 
-        let result = m.lightning(light, position, eyev, normalv);
+        let result = m.lightning(&Sphere::default(), &light, position, eyev, normalv, 1.);
         assert_eq!(
             result,
             Color {
@@ -209,4 +314,69 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn lighting_with_the_surface_in_shadow() {
+        let m = Material::default();
+        let position = point!(0., 0., 0.);
+        let eyev = vector!(0., 0., -1.);
+        let normalv = vector!(0., 0., -1.);
+        let light = PointLight::new(point!(0., 0., -10.), Color::new(1., 1., 1.));
+
+        let result = m.lightning(&Sphere::default(), &light, position, eyev, normalv, 0.);
+        test_color!(result, Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn lighting_with_a_pattern_applied() {
+        let m = Material {
+            pattern: Some(Arc::new(Stripe::new(Color::new(1., 1., 1.), BLACK))),
+            ambient: 1.,
+            diffuse: 0.,
+            specular: 0.,
+            ..Material::default()
+        };
+        let object = Sphere::default();
+        let eyev = vector!(0., 0., -1.);
+        let normalv = vector!(0., 0., -1.);
+        let light = PointLight::new(point!(0., 0., -10.), Color::new(1., 1., 1.));
+        let c1 = m.lightning(&object, &light, point!(0.9, 0., 0.), eyev, normalv, 1.);
+        let c2 = m.lightning(&object, &light, point!(1.1, 0., 0.), eyev, normalv, 1.);
+        assert_eq!(c1, Color::new(1., 1., 1.));
+        assert_eq!(c2, Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn lighting_samples_an_area_light() {
+        let mut light = AreaLight::rectangle(
+            point!(-0.5, -0.5, -5.),
+            vector!(1., 0., 0.),
+            2,
+            vector!(0., 1., 0.),
+            2,
+            Color::new(1., 1., 1.),
+        );
+        light.jitter = false;
+        let object = Sphere::default();
+        let m = Material {
+            ambient: 0.1,
+            diffuse: 0.9,
+            specular: 0.,
+            ..Material::default()
+        };
+        let eye = point!(0., 0., -5.);
+        let cases = [
+            (point!(0., 0., -1.), Color::new(0.9965, 0.9965, 0.9965)),
+            (
+                point!(0., FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+                Color::new(0.62318, 0.62318, 0.62318),
+            ),
+        ];
+        for (p, expected) in cases {
+            let eyev = (eye - p).normalize();
+            let normalv = vector!(p.x, p.y, p.z);
+            let result = m.lightning(&object, &light, p, eyev, normalv, 1.);
+            test_color!(result, expected);
+        }
+    }
 }
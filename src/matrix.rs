@@ -1,6 +1,5 @@
 use crate::tuple::Tuple;
-use core::ops::{Mul};
-
+use core::ops::Mul;
 
 #[derive(Debug, PartialEq)]
 pub struct Matrix2([[f64; 2]; 2]);
@@ -65,6 +64,7 @@ impl Matrix3 {
 pub struct Matrix4(pub [[f64; 4]; 4]);
 
 impl Matrix4 {
+    #[allow(clippy::result_unit_err)]
     pub fn inverse(&self) -> Result<Self, ()> {
         let mut m = Matrix4([[0.; 4]; 4]);
 
@@ -109,7 +109,7 @@ impl Matrix4 {
         ])
     }
 
-    fn shearing(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Matrix4 {
+    pub fn shearing(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Matrix4 {
         Matrix4([
             [1., a, b, 0.],
             [c, 1., d, 0.],
@@ -118,6 +118,21 @@ impl Matrix4 {
         ])
     }
 
+    /// Orients the world relative to an eye at `from` looking towards `to`.
+    pub fn view_transform(from: Tuple, to: Tuple, up: Tuple) -> Matrix4 {
+        let forward = (to - from).normalize();
+        let left = Tuple::cross(forward, up.normalize());
+        let true_up = Tuple::cross(left, forward);
+        let orientation = Matrix4([
+            [left.x, left.y, left.z, 0.],
+            [true_up.x, true_up.y, true_up.z, 0.],
+            [-forward.x, -forward.y, -forward.z, 0.],
+            [0., 0., 0., 1.],
+        ]);
+
+        orientation * Matrix4::translate(-from.x, -from.y, -from.z)
+    }
+
     pub fn transpose(&self) -> Self {
         let mut output = Self([[0.; 4]; 4]);
         for row in 0..4 {
@@ -668,4 +683,53 @@ mod tests {
 
         test_point!(t * p, point!(15., 0., 7.));
     }
+
+    #[test]
+    fn view_transform_for_default_orientation() {
+        let from = point!(0., 0., 0.);
+        let to = point!(0., 0., -1.);
+        let up = vector!(0., 1., 0.);
+        assert_eq!(Matrix4::view_transform(from, to, up), Matrix4::identity());
+    }
+
+    #[test]
+    fn view_transform_looking_in_positive_z() {
+        let from = point!(0., 0., 0.);
+        let to = point!(0., 0., 1.);
+        let up = vector!(0., 1., 0.);
+        assert_eq!(
+            Matrix4::view_transform(from, to, up),
+            Matrix4::scaling(-1., 1., -1.)
+        );
+    }
+
+    #[test]
+    fn view_transform_moves_the_world() {
+        let from = point!(0., 0., 8.);
+        let to = point!(0., 0., 0.);
+        let up = vector!(0., 1., 0.);
+        assert_eq!(
+            Matrix4::view_transform(from, to, up),
+            Matrix4::translate(0., 0., -8.)
+        );
+    }
+
+    #[test]
+    fn arbitrary_view_transform() {
+        let from = point!(1., 3., 2.);
+        let to = point!(4., -2., 8.);
+        let up = vector!(1., 1., 0.);
+        let t = Matrix4::view_transform(from, to, up);
+        let expected = [
+            [-0.50709, 0.50709, 0.67612, -2.36643],
+            [0.76772, 0.60609, 0.12122, -2.82843],
+            [-0.35857, 0.59761, -0.71714, 0.],
+            [0., 0., 0., 1.],
+        ];
+        for (row, expected_row) in t.0.iter().zip(expected.iter()) {
+            for (value, expected_value) in row.iter().zip(expected_row.iter()) {
+                assert!(equal(*value, *expected_value));
+            }
+        }
+    }
 }
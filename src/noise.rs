@@ -0,0 +1,391 @@
+use std::fmt::Debug;
+
+use crate::{rng::Rng, tuple::Tuple, vector};
+
+/// A smooth pseudo-random field over 3D space, plus the fractal sums built on it.
+pub trait Noise: Debug + Send + Sync {
+    /// Roughly in `[-1, 1]`, varying over distances of about one unit.
+    fn noise(&self, point: Tuple) -> f64;
+
+    /// Fractal Brownian motion: `octaves` layers of noise, each at twice the frequency and
+    /// half the amplitude of the last, normalized back to roughly `[-1, 1]`. No octaves
+    /// gives 0.
+    fn fbm(&self, point: Tuple, octaves: usize) -> f64 {
+        if octaves == 0 {
+            return 0.;
+        }
+        let (mut sum, mut amplitude, mut frequency, mut total) = (0., 1., 1., 0.);
+        for _ in 0..octaves {
+            sum += self.noise(point * frequency) * amplitude;
+            total += amplitude;
+            amplitude /= 2.;
+            frequency *= 2.;
+        }
+        sum / total
+    }
+
+    /// Like `fbm` but summing absolute values, which gives the creased look of marble veins
+    /// and flames. Always in `[0, 1]`, roughly.
+    fn turbulence(&self, point: Tuple, octaves: usize) -> f64 {
+        if octaves == 0 {
+            return 0.;
+        }
+        let (mut sum, mut amplitude, mut frequency, mut total) = (0., 1., 1., 0.);
+        for _ in 0..octaves {
+            sum += self.noise(point * frequency).abs() * amplitude;
+            total += amplitude;
+            amplitude /= 2.;
+            frequency *= 2.;
+        }
+        sum / total
+    }
+}
+
+/// 0 to 255 shuffled by `seed`, written out twice so lookups can run past 255 without wrapping.
+fn permutation(seed: u64) -> [u8; 512] {
+    let mut table: Vec<u8> = (0..=255).collect();
+    let mut rng = Rng::new(seed);
+    for i in (1..table.len()).rev() {
+        table.swap(i, rng.below(i + 1));
+    }
+
+    let mut permutation = [0; 512];
+    for (i, p) in permutation.iter_mut().enumerate() {
+        *p = table[i % 256];
+    }
+    permutation
+}
+
+/// Ken Perlin's improved gradient noise over a permutation shuffled from a seed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Perlin {
+    permutation: [u8; 512],
+}
+
+impl Default for Perlin {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// Dot product of the offset with one of twelve gradients picked by `hash`.
+fn grad(hash: u8, x: f64, y: f64, z: f64) -> f64 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: permutation(seed),
+        }
+    }
+
+    fn hash(&self, i: usize) -> usize {
+        self.permutation[i] as usize
+    }
+}
+
+impl Noise for Perlin {
+    /// Zero at every integer lattice point.
+    fn noise(&self, point: Tuple) -> f64 {
+        let (xf, yf, zf) = (point.x.floor(), point.y.floor(), point.z.floor());
+        let (xi, yi, zi) = (
+            xf.rem_euclid(256.) as usize,
+            yf.rem_euclid(256.) as usize,
+            zf.rem_euclid(256.) as usize,
+        );
+        let (x, y, z) = (point.x - xf, point.y - yf, point.z - zf);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let a = self.hash(xi) + yi;
+        let aa = self.hash(a) + zi;
+        let ab = self.hash(a + 1) + zi;
+        let b = self.hash(xi + 1) + yi;
+        let ba = self.hash(b) + zi;
+        let bb = self.hash(b + 1) + zi;
+
+        let p = &self.permutation;
+        lerp(
+            w,
+            lerp(
+                v,
+                lerp(u, grad(p[aa], x, y, z), grad(p[ba], x - 1., y, z)),
+                lerp(u, grad(p[ab], x, y - 1., z), grad(p[bb], x - 1., y - 1., z)),
+            ),
+            lerp(
+                v,
+                lerp(
+                    u,
+                    grad(p[aa + 1], x, y, z - 1.),
+                    grad(p[ba + 1], x - 1., y, z - 1.),
+                ),
+                lerp(
+                    u,
+                    grad(p[ab + 1], x, y - 1., z - 1.),
+                    grad(p[bb + 1], x - 1., y - 1., z - 1.),
+                ),
+            ),
+        )
+    }
+}
+
+/// The twelve edge midpoints of a cube, used as simplex gradients.
+const GRADIENTS: [[f64; 3]; 12] = [
+    [1., 1., 0.],
+    [-1., 1., 0.],
+    [1., -1., 0.],
+    [-1., -1., 0.],
+    [1., 0., 1.],
+    [-1., 0., 1.],
+    [1., 0., -1.],
+    [-1., 0., -1.],
+    [0., 1., 1.],
+    [0., -1., 1.],
+    [0., 1., -1.],
+    [0., -1., -1.],
+];
+
+/// Ken Perlin's simplex noise: cheaper than `Perlin` in 3D and without its axis-aligned
+/// artifacts. Follows Stefan Gustavson's reference implementation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Simplex {
+    permutation: [u8; 512],
+}
+
+impl Default for Simplex {
+    fn default() -> Self {
+        Self::new(0)
+    }
+}
+
+impl Simplex {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            permutation: permutation(seed),
+        }
+    }
+
+    /// Contribution of one simplex corner at offset `(x, y, z)` from the point.
+    fn corner(&self, gi: usize, x: f64, y: f64, z: f64) -> f64 {
+        let t = 0.6 - x * x - y * y - z * z;
+        if t < 0. {
+            return 0.;
+        }
+        let g = GRADIENTS[self.permutation[gi] as usize % 12];
+        t.powi(4) * (g[0] * x + g[1] * y + g[2] * z)
+    }
+}
+
+impl Noise for Simplex {
+    fn noise(&self, point: Tuple) -> f64 {
+        const F3: f64 = 1. / 3.;
+        const G3: f64 = 1. / 6.;
+
+        // skew into the grid of cubes, each split into six tetrahedra
+        let s = (point.x + point.y + point.z) * F3;
+        let (i, j, k) = (
+            (point.x + s).floor(),
+            (point.y + s).floor(),
+            (point.z + s).floor(),
+        );
+        let t = (i + j + k) * G3;
+        let (x0, y0, z0) = (point.x - (i - t), point.y - (j - t), point.z - (k - t));
+
+        // which tetrahedron: walk the axes in decreasing order of offset
+        let (i1, j1, k1, i2, j2, k2) = if x0 >= y0 {
+            if y0 >= z0 {
+                (1, 0, 0, 1, 1, 0)
+            } else if x0 >= z0 {
+                (1, 0, 0, 1, 0, 1)
+            } else {
+                (0, 0, 1, 1, 0, 1)
+            }
+        } else if y0 < z0 {
+            (0, 0, 1, 0, 1, 1)
+        } else if x0 < z0 {
+            (0, 1, 0, 0, 1, 1)
+        } else {
+            (0, 1, 0, 1, 1, 0)
+        };
+
+        let (x1, y1, z1) = (
+            x0 - i1 as f64 + G3,
+            y0 - j1 as f64 + G3,
+            z0 - k1 as f64 + G3,
+        );
+        let (x2, y2, z2) = (
+            x0 - i2 as f64 + 2. * G3,
+            y0 - j2 as f64 + 2. * G3,
+            z0 - k2 as f64 + 2. * G3,
+        );
+        let (x3, y3, z3) = (x0 - 1. + 3. * G3, y0 - 1. + 3. * G3, z0 - 1. + 3. * G3);
+
+        let ii = i.rem_euclid(256.) as usize;
+        let jj = j.rem_euclid(256.) as usize;
+        let kk = k.rem_euclid(256.) as usize;
+        let p = |i: usize| self.permutation[i] as usize;
+
+        let n0 = self.corner(ii + p(jj + p(kk)), x0, y0, z0);
+        let n1 = self.corner(ii + i1 + p(jj + j1 + p(kk + k1)), x1, y1, z1);
+        let n2 = self.corner(ii + i2 + p(jj + j2 + p(kk + k2)), x2, y2, z2);
+        let n3 = self.corner(ii + 1 + p(jj + 1 + p(kk + 1)), x3, y3, z3);
+
+        // scales the result to about [-1, 1]
+        32. * (n0 + n1 + n2 + n3)
+    }
+}
+
+/// Fakes a bumpy surface by tilting normals along the gradient of a noise field, without
+/// adding geometry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BumpMap {
+    noise: Perlin,
+    /// How strongly normals tilt; small values such as 0.1 to 0.5 look like a rough finish.
+    pub scale: f64,
+    /// Bumps per unit of object space.
+    pub frequency: f64,
+}
+
+impl BumpMap {
+    pub fn new(scale: f64, frequency: f64, seed: u64) -> Self {
+        Self {
+            noise: Perlin::new(seed),
+            scale,
+            frequency,
+        }
+    }
+
+    /// Tilts the object-space `normal` at `point` away from the slope of the noise there.
+    pub fn perturb(&self, point: Tuple, normal: Tuple) -> Tuple {
+        const H: f64 = 0.001;
+        let p = point * self.frequency;
+        let sample = |dx: f64, dy: f64, dz: f64| self.noise.noise(p + vector!(dx, dy, dz));
+        let gradient = vector!(
+            (sample(H, 0., 0.) - sample(-H, 0., 0.)) / (2. * H),
+            (sample(0., H, 0.) - sample(0., -H, 0.)) / (2. * H),
+            (sample(0., 0., H) - sample(0., 0., -H)) / (2. * H)
+        );
+
+        let mut normal = normal;
+        normal.w = 0.;
+        let normal = normal.normalize();
+        // only the part of the slope along the surface tilts the normal
+        let tangential = gradient - normal * gradient.dot(normal);
+        (normal - tangential * self.scale).normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{point, tuple::Tuple, vector};
+
+    use super::{BumpMap, Noise, Perlin, Simplex};
+
+    #[test]
+    fn perlin_noise_is_zero_on_lattice_points() {
+        let p = Perlin::new(3);
+        assert_eq!(p.noise(point!(0., 0., 0.)), 0.);
+        assert_eq!(p.noise(point!(4., -2., 7.)), 0.);
+    }
+
+    #[test]
+    fn perlin_noise_is_deterministic_and_bounded() {
+        let a = Perlin::new(11);
+        let b = Perlin::new(11);
+        let c = Perlin::new(12);
+        let mut differs = false;
+        for i in 0..200 {
+            let p = point!(i as f64 * 0.37, i as f64 * -0.21, i as f64 * 0.13);
+            let n = a.noise(p);
+            assert_eq!(n, b.noise(p));
+            assert!((-1.1..=1.1).contains(&n));
+            differs |= n != c.noise(p);
+        }
+        assert!(differs);
+    }
+
+    #[test]
+    fn perlin_noise_is_continuous() {
+        let p = Perlin::default();
+        let a = p.noise(point!(1.5, 2.25, 3.75));
+        let b = p.noise(point!(1.5001, 2.25, 3.75));
+        assert!((a - b).abs() < 0.01);
+    }
+
+    #[test]
+    fn simplex_noise_is_deterministic_bounded_and_continuous() {
+        let a = Simplex::new(5);
+        let b = Simplex::new(5);
+        let mut nonzero = false;
+        for i in 0..200 {
+            let p = point!(i as f64 * 0.31, i as f64 * 0.17 - 9., i as f64 * -0.29);
+            let n = a.noise(p);
+            assert_eq!(n, b.noise(p));
+            assert!((-1.1..=1.1).contains(&n));
+            nonzero |= n != 0.;
+        }
+        assert!(nonzero);
+
+        let n = a.noise(point!(0.3, 0.7, 1.9));
+        assert!((n - a.noise(point!(0.3001, 0.7, 1.9))).abs() < 0.01);
+    }
+
+    #[test]
+    fn fbm_with_one_octave_is_plain_noise() {
+        let p = Perlin::new(9);
+        let at = point!(1.3, 2.7, -0.4);
+        assert_eq!(p.fbm(at, 1), p.noise(at));
+        assert_eq!(p.turbulence(at, 1), p.noise(at).abs());
+    }
+
+    #[test]
+    fn no_octaves_is_flat() {
+        let s = Simplex::default();
+        let at = point!(0.3, 1.1, -2.);
+        assert_eq!(s.fbm(at, 0), 0.);
+        assert_eq!(s.turbulence(at, 0), 0.);
+    }
+
+    #[test]
+    fn turbulence_is_never_negative() {
+        let s = Simplex::default();
+        for i in 0..100 {
+            let at = point!(i as f64 * 0.7, 0.5, i as f64 * -0.3);
+            assert!(s.turbulence(at, 4) >= 0.);
+            assert!((-1.1..=1.1).contains(&s.fbm(at, 4)));
+        }
+    }
+
+    #[test]
+    fn bump_map_tilts_normals_but_keeps_them_unit_length() {
+        let bump = BumpMap::new(0.3, 4., 2);
+        let n = vector!(0., 1., 0.);
+        let mut tilted = false;
+        for i in 0..50 {
+            let at = point!(i as f64 * 0.13, 0., i as f64 * 0.07);
+            let b = bump.perturb(at, n);
+            assert!((b.magnitude() - 1.).abs() < 1e-9);
+            assert!(b.dot(n) > 0.);
+            tilted |= b != n;
+        }
+        assert!(tilted);
+        assert_eq!(BumpMap::new(0., 4., 2).perturb(point!(0.3, 0., 0.6), n), n);
+    }
+}
@@ -0,0 +1,465 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use crate::{
+    group::Group,
+    material::Material,
+    matrix::Matrix4,
+    point,
+    shape::Shape,
+    triangle::{SmoothTriangle, Triangle},
+    tuple::Tuple,
+    vector,
+};
+
+/// A line of an OBJ file that was skipped, and why.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjWarning {
+    pub line: usize,
+    pub message: String,
+}
+
+impl Display for ObjWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// One corner of a face: indices into the vertex, texture and normal lists.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaceVertex {
+    pub vertex: usize,
+    pub texture: Option<usize>,
+    pub normal: Option<usize>,
+}
+
+/// Faces collected under a `g` or `o` statement, already split into triangles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjGroup {
+    pub name: String,
+    pub triangles: Vec<[FaceVertex; 3]>,
+}
+
+/// The parsed contents of a Wavefront OBJ file. Indices in the file are 1-based;
+/// here they are 0-based.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjFile {
+    pub vertices: Vec<Tuple>,
+    pub normals: Vec<Tuple>,
+    pub texture_coords: Vec<(f64, f64)>,
+    pub groups: Vec<ObjGroup>,
+    pub warnings: Vec<ObjWarning>,
+}
+
+fn parse_floats<const N: usize>(args: &[&str]) -> Option<[f64; N]> {
+    if args.len() < N {
+        return None;
+    }
+
+    let mut values = [0.; N];
+    for (value, arg) in values.iter_mut().zip(args) {
+        *value = arg.parse().ok()?;
+    }
+    Some(values)
+}
+
+/// Resolves a 1-based (or negative, relative to the end) index against a list of `len` items.
+fn resolve_index(index: &str, len: usize) -> Result<usize, String> {
+    let i: i64 = index
+        .parse()
+        .map_err(|_| format!("invalid index `{}`", index))?;
+
+    let resolved = if i < 0 { len as i64 + i } else { i - 1 };
+    if resolved < 0 || resolved >= len as i64 {
+        return Err(format!("index {} out of range", i));
+    }
+
+    Ok(resolved as usize)
+}
+
+impl ObjFile {
+    pub fn parse(input: &str) -> Self {
+        let mut obj = ObjFile {
+            vertices: vec![],
+            normals: vec![],
+            texture_coords: vec![],
+            groups: vec![ObjGroup {
+                name: String::from("default"),
+                triangles: vec![],
+            }],
+            warnings: vec![],
+        };
+
+        for (number, line) in input.lines().enumerate() {
+            if let Err(message) = obj.parse_line(line) {
+                obj.warnings.push(ObjWarning {
+                    line: number + 1,
+                    message,
+                });
+            }
+        }
+
+        obj
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    fn parse_line(&mut self, line: &str) -> Result<(), String> {
+        let line = line.split('#').next().unwrap_or("");
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            return Ok(());
+        };
+        let args: Vec<&str> = words.collect();
+
+        match keyword {
+            "v" => {
+                let [x, y, z] = parse_floats(&args).ok_or("malformed vertex")?;
+                self.vertices.push(point!(x, y, z));
+            }
+            "vn" => {
+                let [x, y, z] = parse_floats(&args).ok_or("malformed vertex normal")?;
+                self.normals.push(vector!(x, y, z));
+            }
+            "vt" => {
+                let [u, v] = parse_floats(&args).ok_or("malformed texture coordinate")?;
+                self.texture_coords.push((u, v));
+            }
+            "f" => {
+                if args.len() < 3 {
+                    return Err(String::from("face needs at least three vertices"));
+                }
+
+                let corners = args
+                    .iter()
+                    .map(|arg| self.parse_face_vertex(arg))
+                    .collect::<Result<Vec<_>, _>>()?;
+
+                // fan triangulation around the first corner
+                let group = self.groups.last_mut().unwrap();
+                for i in 1..corners.len() - 1 {
+                    group
+                        .triangles
+                        .push([corners[0], corners[i], corners[i + 1]]);
+                }
+            }
+            "g" | "o" => {
+                self.groups.push(ObjGroup {
+                    name: args.join(" "),
+                    triangles: vec![],
+                });
+            }
+            other => return Err(format!("unsupported statement `{}`", other)),
+        }
+
+        Ok(())
+    }
+
+    fn parse_face_vertex(&self, arg: &str) -> Result<FaceVertex, String> {
+        let mut parts = arg.split('/');
+        let vertex = resolve_index(parts.next().unwrap_or(""), self.vertices.len())?;
+
+        let texture = match parts.next() {
+            Some(t) if !t.is_empty() => Some(resolve_index(t, self.texture_coords.len())?),
+            _ => None,
+        };
+        let normal = match parts.next() {
+            Some(n) if !n.is_empty() => Some(resolve_index(n, self.normals.len())?),
+            _ => None,
+        };
+
+        Ok(FaceVertex {
+            vertex,
+            texture,
+            normal,
+        })
+    }
+
+    /// Builds a triangle for a face, smooth when every corner carries a normal.
+    fn triangle(&self, face: &[FaceVertex; 3]) -> Box<dyn Shape> {
+        let [a, b, c] = face;
+        let (p1, p2, p3) = (
+            self.vertices[a.vertex],
+            self.vertices[b.vertex],
+            self.vertices[c.vertex],
+        );
+
+        match (a.normal, b.normal, c.normal) {
+            (Some(n1), Some(n2), Some(n3)) => Box::new(SmoothTriangle::new(
+                p1,
+                p2,
+                p3,
+                self.normals[n1],
+                self.normals[n2],
+                self.normals[n3],
+            )),
+            _ => Box::new(Triangle::new(p1, p2, p3)),
+        }
+    }
+
+    /// Triangles of the named group, if it exists.
+    pub fn group(&self, name: &str) -> Option<Vec<Box<dyn Shape>>> {
+        self.groups
+            .iter()
+            .find(|g| g.name == name)
+            .map(|g| g.triangles.iter().map(|f| self.triangle(f)).collect())
+    }
+
+    /// The whole file as one group, with a child group for each named group in the file.
+    pub fn to_group(&self, transform: Matrix4, material: Material) -> Group {
+        let mut mesh = Group::default();
+
+        for group in self.groups.iter().filter(|g| !g.triangles.is_empty()) {
+            let mut child = Group::default();
+            for face in &group.triangles {
+                let mut triangle = self.triangle(face);
+                *triangle.material_mut() = material.clone();
+                child.add_boxed_child(triangle);
+            }
+            mesh.add_child(child);
+        }
+
+        mesh.set_transform(transform);
+        mesh
+    }
+
+    /// Every triangle in the file, placed with `transform` and shaded with `material`.
+    pub fn to_shapes(&self, transform: Matrix4, material: Material) -> Vec<Box<dyn Shape>> {
+        self.groups
+            .iter()
+            .flat_map(|g| g.triangles.iter())
+            .map(|face| {
+                let mut triangle = self.triangle(face);
+                triangle.set_transform(transform);
+                *triangle.material_mut() = material.clone();
+                triangle
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        material::Material, matrix::Matrix4, point, ray::Ray, shape::Shape, tuple::Tuple, vector,
+        Color,
+    };
+
+    use super::{FaceVertex, ObjFile, ObjWarning};
+
+    fn corner(vertex: usize) -> FaceVertex {
+        FaceVertex {
+            vertex,
+            texture: None,
+            normal: None,
+        }
+    }
+
+    #[test]
+    fn ignoring_unrecognized_lines() {
+        let gibberish = "There was a young lady named Bright
+who traveled much faster than light.
+";
+        let obj = ObjFile::parse(gibberish);
+        assert!(obj.vertices.is_empty());
+        assert_eq!(obj.warnings.len(), 2);
+        assert_eq!(obj.warnings[1].line, 2);
+    }
+
+    #[test]
+    fn vertex_records() {
+        let file = "v -1 1 0
+v -1.0000 0.5000 0.0000
+v 1 0 0
+v 1 1 0
+";
+        let obj = ObjFile::parse(file);
+        assert_eq!(obj.vertices[0], point!(-1., 1., 0.));
+        assert_eq!(obj.vertices[1], point!(-1., 0.5, 0.));
+        assert_eq!(obj.vertices[2], point!(1., 0., 0.));
+        assert_eq!(obj.vertices[3], point!(1., 1., 0.));
+    }
+
+    #[test]
+    fn parsing_triangle_faces() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+f 1 2 3
+f 1 3 4
+";
+        let obj = ObjFile::parse(file);
+        let g = &obj.groups[0];
+        assert_eq!(g.triangles[0], [corner(0), corner(1), corner(2)]);
+        assert_eq!(g.triangles[1], [corner(0), corner(2), corner(3)]);
+        assert!(obj.warnings.is_empty());
+    }
+
+    #[test]
+    fn triangulating_polygons() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+v 0 2 0
+
+f 1 2 3 4 5
+";
+        let obj = ObjFile::parse(file);
+        let g = &obj.groups[0];
+        assert_eq!(g.triangles.len(), 3);
+        assert_eq!(g.triangles[0], [corner(0), corner(1), corner(2)]);
+        assert_eq!(g.triangles[1], [corner(0), corner(2), corner(3)]);
+        assert_eq!(g.triangles[2], [corner(0), corner(3), corner(4)]);
+    }
+
+    #[test]
+    fn triangles_in_groups() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+o SecondGroup
+f 1 3 4
+";
+        let obj = ObjFile::parse(file);
+        let first = obj.group("FirstGroup").unwrap();
+        let second = obj.group("SecondGroup").unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(second.len(), 1);
+        assert!(obj.group("default").unwrap().is_empty());
+        assert!(obj.group("Missing").is_none());
+    }
+
+    #[test]
+    fn vertex_normal_records() {
+        let file = "vn 0 0 1
+vn 0.707 0 -0.707
+vn 1 2 3
+";
+        let obj = ObjFile::parse(file);
+        assert_eq!(obj.normals[0], vector!(0., 0., 1.));
+        assert_eq!(obj.normals[1], vector!(0.707, 0., -0.707));
+        assert_eq!(obj.normals[2], vector!(1., 2., 3.));
+    }
+
+    #[test]
+    fn faces_with_texture_and_normal_indices() {
+        let file = "v 0 1 0
+v -1 0 0
+v 1 0 0
+
+vt 0 0
+vt 1 0
+
+vn -1 0 0
+vn 1 0 0
+vn 0 1 0
+
+f 1//3 2//1 3//2
+f 1/1/3 2/2/1 3/-1/2
+";
+        let obj = ObjFile::parse(file);
+        assert!(obj.warnings.is_empty());
+        let g = &obj.groups[0];
+        assert_eq!(
+            g.triangles[0][0],
+            FaceVertex {
+                vertex: 0,
+                texture: None,
+                normal: Some(2),
+            }
+        );
+        assert_eq!(g.triangles[1][2].texture, Some(1));
+
+        let shapes = obj.group("default").unwrap();
+        let r = Ray {
+            origin: point!(-0.2, 0.3, -2.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = shapes[0].intersect(r);
+        assert!(xs.0[0].uv.is_some());
+    }
+
+    #[test]
+    fn malformed_lines_are_reported_with_line_numbers() {
+        let file = "v 1 2
+v 0 0 0
+v 1 0 0
+v 0 1 0
+f 1 2 9
+f 1 2
+vt x y
+mtllib scene.mtl
+f 1 2 3
+";
+        let obj = ObjFile::parse(file);
+        let lines: Vec<usize> = obj.warnings.iter().map(|w| w.line).collect();
+        assert_eq!(lines, vec![1, 5, 6, 7, 8]);
+        assert_eq!(obj.groups[0].triangles.len(), 1);
+        assert_eq!(
+            obj.warnings[4],
+            ObjWarning {
+                line: 8,
+                message: String::from("unsupported statement `mtllib`"),
+            }
+        );
+    }
+
+    #[test]
+    fn mesh_takes_a_transform_and_material() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+f 1 2 3 4
+";
+        let obj = ObjFile::parse(file);
+        let material = Material {
+            color: Color::new(1., 0., 0.),
+            ..Default::default()
+        };
+        let shapes = obj.to_shapes(Matrix4::translate(0., 0., 5.), material.clone());
+        assert_eq!(shapes.len(), 2);
+        for shape in &shapes {
+            assert_eq!(shape.transform(), Matrix4::translate(0., 0., 5.));
+            assert_eq!(*shape.material(), material);
+        }
+    }
+
+    #[test]
+    fn converting_an_obj_file_to_a_group() {
+        let file = "v -1 1 0
+v -1 0 0
+v 1 0 0
+v 1 1 0
+
+g FirstGroup
+f 1 2 3
+g SecondGroup
+f 1 3 4
+";
+        let obj = ObjFile::parse(file);
+        let g = obj.to_group(Matrix4::translate(0., 0., 5.), Material::default());
+        assert_eq!(g.children().len(), 2);
+        assert_eq!(g.transform(), Matrix4::translate(0., 0., 5.));
+
+        let r = Ray {
+            origin: point!(-0.5, 0.5, -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = g.intersect(r);
+        assert_eq!(xs.0.len(), 1);
+        assert_eq!(xs.0[0].t, 10.);
+        assert_eq!(
+            xs.0[0].object.parent_transform(),
+            Matrix4::translate(0., 0., 5.)
+        );
+    }
+}
@@ -0,0 +1,709 @@
+use std::fmt::Debug;
+
+use std::f64::consts::PI;
+
+use crate::{
+    matrix::Matrix4,
+    noise::{Noise, Perlin, Simplex},
+    shape::Shape,
+    transform::Transform,
+    tuple::Tuple,
+    vector, Color,
+};
+
+/// A color that varies over a surface. Implementors only deal with pattern space; getting
+/// there from world space, through the object's transform and then the pattern's own, is
+/// done here.
+pub trait Pattern: Debug + Send + Sync {
+    /// The color at a point already transformed into pattern space.
+    fn pattern_at(&self, point: Tuple) -> Color;
+
+    fn transformation(&self) -> &Transform;
+
+    fn transformation_mut(&mut self) -> &mut Transform;
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.transformation_mut().set(transform);
+    }
+
+    /// The color at a point in the space of whatever holds this pattern: an object, or
+    /// another pattern when nested.
+    fn pattern_at_parent(&self, point: Tuple) -> Color {
+        self.pattern_at(self.transformation().inverse() * point)
+    }
+
+    fn pattern_at_shape(&self, object: &dyn Shape, world_point: Tuple) -> Color {
+        self.pattern_at_parent(object.world_to_object(world_point))
+    }
+}
+
+/// Lets a pattern be nested without boxing it by hand.
+impl<P: Pattern + 'static> From<P> for Box<dyn Pattern> {
+    fn from(pattern: P) -> Self {
+        Box::new(pattern)
+    }
+}
+
+/// Lets a plain color stand wherever a pattern is expected.
+impl From<Color> for Box<dyn Pattern> {
+    fn from(color: Color) -> Self {
+        Box::new(Solid::new(color))
+    }
+}
+
+/// The same color everywhere; the leaf that nested patterns bottom out in.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Solid {
+    pub color: Color,
+    transform: Transform,
+}
+
+impl Solid {
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl Pattern for Solid {
+    fn pattern_at(&self, _point: Tuple) -> Color {
+        self.color
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+/// Alternates between `a` and `b` every unit along x.
+#[derive(Debug)]
+pub struct Stripe {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    transform: Transform,
+}
+
+/// Blends linearly from `a` to `b` along x, starting over every unit.
+#[derive(Debug)]
+pub struct Gradient {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    transform: Transform,
+}
+
+/// Concentric rings around the y axis, alternating every unit of distance.
+#[derive(Debug)]
+pub struct Ring {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    transform: Transform,
+}
+
+/// Unit cubes alternating in all three dimensions.
+#[derive(Debug)]
+pub struct Checkers {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    transform: Transform,
+}
+
+/// Blends from `a` to `b` with distance from the y axis, starting over every unit.
+#[derive(Debug)]
+pub struct RadialGradient {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    transform: Transform,
+}
+
+/// Whether `value` falls in an even-numbered unit interval, counting from zero
+/// in both directions.
+fn is_even(value: f64) -> bool {
+    value.floor().rem_euclid(2.) == 0.
+}
+
+fn blend(a: Color, b: Color, fraction: f64) -> Color {
+    a + (b - a) * fraction
+}
+
+/// The constructor shared by every two-color pattern. Either side may be a plain
+/// `Color` or another pattern.
+macro_rules! two_color_pattern {
+    ($name: ident) => {
+        impl $name {
+            pub fn new(a: impl Into<Box<dyn Pattern>>, b: impl Into<Box<dyn Pattern>>) -> Self {
+                Self {
+                    a: a.into(),
+                    b: b.into(),
+                    transform: Transform::default(),
+                }
+            }
+        }
+    };
+}
+
+two_color_pattern!(Stripe);
+
+impl Pattern for Stripe {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        if is_even(point.x) {
+            self.a.pattern_at_parent(point)
+        } else {
+            self.b.pattern_at_parent(point)
+        }
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+two_color_pattern!(Gradient);
+
+impl Pattern for Gradient {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        blend(
+            self.a.pattern_at_parent(point),
+            self.b.pattern_at_parent(point),
+            point.x - point.x.floor(),
+        )
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+two_color_pattern!(Ring);
+
+impl Pattern for Ring {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        if is_even((point.x * point.x + point.z * point.z).sqrt()) {
+            self.a.pattern_at_parent(point)
+        } else {
+            self.b.pattern_at_parent(point)
+        }
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+two_color_pattern!(Checkers);
+
+impl Pattern for Checkers {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        if is_even(point.x.floor() + point.y.floor() + point.z.floor()) {
+            self.a.pattern_at_parent(point)
+        } else {
+            self.b.pattern_at_parent(point)
+        }
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+two_color_pattern!(RadialGradient);
+
+impl Pattern for RadialGradient {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        let distance = (point.x * point.x + point.z * point.z).sqrt();
+        blend(
+            self.a.pattern_at_parent(point),
+            self.b.pattern_at_parent(point),
+            distance - distance.floor(),
+        )
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+/// The average of two patterns laid over each other.
+#[derive(Debug)]
+pub struct Blended {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    transform: Transform,
+}
+
+two_color_pattern!(Blended);
+
+impl Pattern for Blended {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        blend(
+            self.a.pattern_at_parent(point),
+            self.b.pattern_at_parent(point),
+            0.5,
+        )
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+/// Another pattern looked up at points jittered by noise, so its edges wander.
+#[derive(Debug)]
+pub struct Perturbed {
+    pub pattern: Box<dyn Pattern>,
+    /// How far, at most, a lookup point is pushed.
+    pub scale: f64,
+    noise: Perlin,
+    transform: Transform,
+}
+
+impl Perturbed {
+    pub fn new(pattern: impl Into<Box<dyn Pattern>>, scale: f64, seed: u64) -> Self {
+        Self {
+            pattern: pattern.into(),
+            scale,
+            noise: Perlin::new(seed),
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl Pattern for Perturbed {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        // sample well apart so the three offsets don't move together
+        let jitter = vector!(
+            self.noise.noise(point),
+            self.noise.noise(point + vector!(31.4, 0., 0.)),
+            self.noise.noise(point + vector!(0., 0., 27.2))
+        );
+        self.pattern.pattern_at_parent(point + jitter * self.scale)
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+/// Veins of `b` running through `a` along x, warped by turbulence.
+#[derive(Debug)]
+pub struct Marble {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    /// How far the veins wander; 0 gives straight bands.
+    pub turbulence: f64,
+    noise: Perlin,
+    transform: Transform,
+}
+
+impl Marble {
+    pub fn new(a: impl Into<Box<dyn Pattern>>, b: impl Into<Box<dyn Pattern>>, seed: u64) -> Self {
+        Self {
+            a: a.into(),
+            b: b.into(),
+            turbulence: 5.,
+            noise: Perlin::new(seed),
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl Pattern for Marble {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        let phase = point.x * PI + self.turbulence * self.noise.turbulence(point, 6);
+        blend(
+            self.a.pattern_at_parent(point),
+            self.b.pattern_at_parent(point),
+            (phase.sin() + 1.) / 2.,
+        )
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+/// Growth rings around the y axis, fading from `a` to `b` within each ring, with noise
+/// bending them like grain.
+#[derive(Debug)]
+pub struct Wood {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    /// How far the rings are pushed off true circles.
+    pub grain: f64,
+    noise: Perlin,
+    transform: Transform,
+}
+
+impl Wood {
+    pub fn new(a: impl Into<Box<dyn Pattern>>, b: impl Into<Box<dyn Pattern>>, seed: u64) -> Self {
+        Self {
+            a: a.into(),
+            b: b.into(),
+            grain: 0.3,
+            noise: Perlin::new(seed),
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl Pattern for Wood {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        let distance =
+            (point.x * point.x + point.z * point.z).sqrt() + self.grain * self.noise.noise(point);
+        blend(
+            self.a.pattern_at_parent(point),
+            self.b.pattern_at_parent(point),
+            distance - distance.floor(),
+        )
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+/// Soft patches of `b` (cloud) over `a` (sky) from fractal simplex noise.
+#[derive(Debug)]
+pub struct Clouds {
+    pub a: Box<dyn Pattern>,
+    pub b: Box<dyn Pattern>,
+    /// Layers of detail; more octaves give wispier edges.
+    pub octaves: usize,
+    noise: Simplex,
+    transform: Transform,
+}
+
+impl Clouds {
+    pub fn new(a: impl Into<Box<dyn Pattern>>, b: impl Into<Box<dyn Pattern>>, seed: u64) -> Self {
+        Self {
+            a: a.into(),
+            b: b.into(),
+            octaves: 5,
+            noise: Simplex::new(seed),
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl Pattern for Clouds {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        let density = (self.noise.fbm(point, self.octaves) + 1.) / 2.;
+        blend(
+            self.a.pattern_at_parent(point),
+            self.b.pattern_at_parent(point),
+            density.clamp(0., 1.),
+        )
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        equal, matrix::Matrix4, point, shape::Shape, sphere::Sphere, test_color,
+        transform::Transform, tuple::Tuple, Color, BLACK,
+    };
+
+    use super::{
+        Blended, Checkers, Clouds, Gradient, Marble, Pattern, Perturbed, RadialGradient, Ring,
+        Solid, Stripe, Wood,
+    };
+
+    const WHITE: Color = Color {
+        red: 1.,
+        green: 1.,
+        blue: 1.,
+    };
+
+    /// Returns the pattern-space point it was asked about as a color.
+    #[derive(Debug, Default)]
+    struct TestPattern {
+        transform: Transform,
+    }
+
+    impl Pattern for TestPattern {
+        fn pattern_at(&self, point: Tuple) -> Color {
+            Color::new(point.x, point.y, point.z)
+        }
+
+        fn transformation(&self) -> &Transform {
+            &self.transform
+        }
+
+        fn transformation_mut(&mut self) -> &mut Transform {
+            &mut self.transform
+        }
+    }
+
+    #[test]
+    fn default_pattern_transformation() {
+        let p = TestPattern::default();
+        assert_eq!(p.transformation().matrix(), Matrix4::identity());
+    }
+
+    #[test]
+    fn pattern_with_object_transformation() {
+        let mut shape = Sphere::default();
+        shape.set_transform(Matrix4::scaling(2., 2., 2.));
+        let p = TestPattern::default();
+        let c = p.pattern_at_shape(&shape, point!(2., 3., 4.));
+        test_color!(c, Color::new(1., 1.5, 2.));
+    }
+
+    #[test]
+    fn pattern_with_pattern_transformation() {
+        let shape = Sphere::default();
+        let mut p = TestPattern::default();
+        p.set_transform(Matrix4::scaling(2., 2., 2.));
+        let c = p.pattern_at_shape(&shape, point!(2., 3., 4.));
+        test_color!(c, Color::new(1., 1.5, 2.));
+    }
+
+    #[test]
+    fn pattern_with_object_and_pattern_transformation() {
+        let mut shape = Sphere::default();
+        shape.set_transform(Matrix4::scaling(2., 2., 2.));
+        let mut p = TestPattern::default();
+        p.set_transform(Matrix4::translate(0.5, 1., 1.5));
+        let c = p.pattern_at_shape(&shape, point!(2.5, 3., 3.5));
+        test_color!(c, Color::new(0.75, 0.5, 0.25));
+    }
+
+    #[test]
+    fn stripe_pattern_is_constant_in_y_and_z() {
+        let p = Stripe::new(WHITE, BLACK);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0., 1., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0., 2., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0., 0., 1.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0., 0., 2.)), WHITE);
+    }
+
+    #[test]
+    fn stripe_pattern_alternates_in_x() {
+        let p = Stripe::new(WHITE, BLACK);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0.9, 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(1., 0., 0.)), BLACK);
+        assert_eq!(p.pattern_at(point!(-0.1, 0., 0.)), BLACK);
+        assert_eq!(p.pattern_at(point!(-1., 0., 0.)), BLACK);
+        assert_eq!(p.pattern_at(point!(-1.1, 0., 0.)), WHITE);
+    }
+
+    #[test]
+    fn gradient_linearly_interpolates_between_colors() {
+        let p = Gradient::new(WHITE, BLACK);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.)), WHITE);
+        assert_eq!(
+            p.pattern_at(point!(0.25, 0., 0.)),
+            Color::new(0.75, 0.75, 0.75)
+        );
+        assert_eq!(p.pattern_at(point!(0.5, 0., 0.)), Color::new(0.5, 0.5, 0.5));
+        assert_eq!(
+            p.pattern_at(point!(0.75, 0., 0.)),
+            Color::new(0.25, 0.25, 0.25)
+        );
+    }
+
+    #[test]
+    fn ring_extends_in_both_x_and_z() {
+        let p = Ring::new(WHITE, BLACK);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(1., 0., 0.)), BLACK);
+        assert_eq!(p.pattern_at(point!(0., 0., 1.)), BLACK);
+        // 0.708 = just slightly more than √2/2
+        assert_eq!(p.pattern_at(point!(0.708, 0., 0.708)), BLACK);
+    }
+
+    #[test]
+    fn checkers_repeat_in_x() {
+        let p = Checkers::new(WHITE, BLACK);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0.99, 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(1.01, 0., 0.)), BLACK);
+    }
+
+    #[test]
+    fn checkers_repeat_in_y() {
+        let p = Checkers::new(WHITE, BLACK);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0., 0.99, 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0., 1.01, 0.)), BLACK);
+    }
+
+    #[test]
+    fn checkers_repeat_in_z() {
+        let p = Checkers::new(WHITE, BLACK);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.99)), WHITE);
+        assert_eq!(p.pattern_at(point!(0., 0., 1.01)), BLACK);
+        assert_eq!(p.pattern_at(point!(-0.5, 0., 0.5)), BLACK);
+    }
+
+    #[test]
+    fn radial_gradient_blends_with_distance_from_y_axis() {
+        let p = RadialGradient::new(WHITE, BLACK);
+        assert_eq!(p.pattern_at(point!(0., 5., 0.)), WHITE);
+        test_color!(
+            p.pattern_at(point!(0.3, 0., 0.4)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        test_color!(
+            p.pattern_at(point!(0., 0., 1.25)),
+            Color::new(0.75, 0.75, 0.75)
+        );
+    }
+
+    #[test]
+    fn solid_pattern_is_the_same_everywhere() {
+        let p = Solid::new(WHITE);
+        assert_eq!(p.pattern_at(point!(0., 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(-3.2, 7., 11.5)), WHITE);
+    }
+
+    #[test]
+    fn checkers_of_stripes() {
+        let red = Color::new(1., 0., 0.);
+        let mut stripes = Stripe::new(WHITE, red);
+        stripes.set_transform(Matrix4::scaling(0.25, 0.25, 0.25));
+        let p = Checkers::new(stripes, BLACK);
+        // inside the first checker the stripes alternate every quarter unit
+        assert_eq!(p.pattern_at(point!(0.1, 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(0.3, 0., 0.)), red);
+        assert_eq!(p.pattern_at(point!(0.6, 0., 0.)), WHITE);
+        assert_eq!(p.pattern_at(point!(1.3, 0., 0.)), BLACK);
+    }
+
+    #[test]
+    fn nested_pattern_transforms_stack() {
+        let mut inner = TestPattern::default();
+        inner.set_transform(Matrix4::translate(1., 0., 0.));
+        let mut outer = Blended::new(inner, BLACK);
+        outer.set_transform(Matrix4::scaling(2., 2., 2.));
+        let shape = Sphere::default();
+        // world (4, 2, 0) -> outer (2, 1, 0) -> inner (1, 1, 0), then averaged with black
+        let c = outer.pattern_at_shape(&shape, point!(4., 2., 0.));
+        test_color!(c, Color::new(0.5, 0.5, 0.));
+    }
+
+    #[test]
+    fn blended_pattern_averages_its_children() {
+        let mut horizontal = Stripe::new(WHITE, BLACK);
+        horizontal.set_transform(Matrix4::rotation_y(std::f64::consts::FRAC_PI_2));
+        let p = Blended::new(Stripe::new(WHITE, BLACK), horizontal);
+        assert_eq!(p.pattern_at(point!(0.5, 0., -0.5)), WHITE);
+        test_color!(
+            p.pattern_at(point!(1.5, 0., -0.5)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+        assert_eq!(p.pattern_at(point!(1.5, 0., 0.5)), BLACK);
+    }
+
+    #[test]
+    fn perturbed_pattern_moves_edges_but_keeps_colors() {
+        let p = Perturbed::new(Stripe::new(WHITE, BLACK), 0.5, 1);
+        let mut moved = false;
+        for i in 0..100 {
+            let point = point!(i as f64 * 0.093, 0.4, 0.7);
+            let c = p.pattern_at(point);
+            assert!(c == WHITE || c == BLACK);
+            moved |= c != Stripe::new(WHITE, BLACK).pattern_at(point);
+        }
+        assert!(moved);
+
+        let still = Perturbed::new(WHITE, 0.5, 1);
+        assert_eq!(still.pattern_at(point!(0.3, 0.3, 0.3)), WHITE);
+    }
+
+    fn is_between_white_and_black(c: Color) -> bool {
+        (0. ..=1.).contains(&c.red) && c.red == c.green && c.green == c.blue
+    }
+
+    #[test]
+    fn procedural_textures_blend_between_their_colors() {
+        let textures: [Box<dyn Pattern>; 3] = [
+            Marble::new(WHITE, BLACK, 1).into(),
+            Wood::new(WHITE, BLACK, 1).into(),
+            Clouds::new(WHITE, BLACK, 1).into(),
+        ];
+        for texture in &textures {
+            let mut seen = vec![];
+            for i in 0..100 {
+                let c = texture.pattern_at(point!(i as f64 * 0.173, 0.31, i as f64 * 0.057));
+                assert!(is_between_white_and_black(c));
+                seen.push(c.red);
+            }
+            assert!(seen.iter().any(|&v| v != seen[0]));
+        }
+    }
+
+    #[test]
+    fn procedural_textures_are_seeded() {
+        let at = point!(0.7, 1.3, -2.1);
+        assert_eq!(
+            Marble::new(WHITE, BLACK, 4).pattern_at(at),
+            Marble::new(WHITE, BLACK, 4).pattern_at(at)
+        );
+        assert_ne!(
+            Clouds::new(WHITE, BLACK, 4).pattern_at(at),
+            Clouds::new(WHITE, BLACK, 5).pattern_at(at)
+        );
+    }
+
+    #[test]
+    fn wood_without_grain_is_a_radial_gradient() {
+        let mut wood = Wood::new(WHITE, BLACK, 1);
+        wood.grain = 0.;
+        let radial = RadialGradient::new(WHITE, BLACK);
+        let at = point!(0.3, 2., 0.4);
+        assert_eq!(wood.pattern_at(at), radial.pattern_at(at));
+    }
+}
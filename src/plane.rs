@@ -0,0 +1,166 @@
+use crate::{
+    bounds::BoundingBox,
+    intersection::{Intersection, Intersections},
+    material::Material,
+    point,
+    ray::Ray,
+    shape::{Shape, TransformAccess},
+    transform::Transform,
+    tuple::Tuple,
+    vector, EPSILON,
+};
+
+/// An infinite plane spanning x and z through the origin.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Plane {
+    transform: Transform,
+    pub material: Material,
+}
+
+impl Shape for Plane {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+        // a ray parallel to the plane, or inside it, never crosses it
+        if ray.direction.y.abs() < EPSILON {
+            return Intersections(vec![]);
+        }
+
+        let t = -ray.origin.y / ray.direction.y;
+        Intersections(vec![Intersection::new(t, self)])
+    }
+
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        vector!(0., 1., 0.)
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            point!(f64::NEG_INFINITY, 0., f64::NEG_INFINITY),
+            point!(f64::INFINITY, 0., f64::INFINITY),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::{
+        equal, matrix::Matrix4, point, ray::Ray, shape::Shape, sphere::Sphere, test_point,
+        tuple::Tuple, vector,
+    };
+
+    use super::Plane;
+
+    #[test]
+    fn normal_of_plane_is_constant_everywhere() {
+        let p = Plane::default();
+        assert_eq!(p.local_normal_at(point!(0., 0., 0.)), vector!(0., 1., 0.));
+        assert_eq!(
+            p.local_normal_at(point!(10., 0., -10.)),
+            vector!(0., 1., 0.)
+        );
+        assert_eq!(
+            p.local_normal_at(point!(-5., 0., 150.)),
+            vector!(0., 1., 0.)
+        );
+    }
+
+    #[test]
+    fn intersect_with_ray_parallel_to_plane() {
+        let p = Plane::default();
+        let r = Ray {
+            origin: point!(0., 10., 0.),
+            direction: vector!(0., 0., 1.),
+        };
+        assert!(p.local_intersect(r).0.is_empty());
+    }
+
+    #[test]
+    fn intersect_with_coplanar_ray() {
+        let p = Plane::default();
+        let r = Ray {
+            origin: point!(0., 0., 0.),
+            direction: vector!(0., 0., 1.),
+        };
+        assert!(p.local_intersect(r).0.is_empty());
+    }
+
+    #[test]
+    fn ray_intersecting_plane_from_above() {
+        let p = Plane::default();
+        let r = Ray {
+            origin: point!(0., 1., 0.),
+            direction: vector!(0., -1., 0.),
+        };
+        let xs = p.local_intersect(r);
+        assert_eq!(xs.0.len(), 1);
+        assert_eq!(xs.0[0].t, 1.);
+        assert_eq!(xs.0[0].object, &p as &dyn Shape);
+    }
+
+    #[test]
+    fn ray_intersecting_plane_from_below() {
+        let p = Plane::default();
+        let r = Ray {
+            origin: point!(0., -1., 0.),
+            direction: vector!(0., 1., 0.),
+        };
+        let xs = p.local_intersect(r);
+        assert_eq!(xs.0.len(), 1);
+        assert_eq!(xs.0[0].t, 1.);
+        assert_eq!(xs.0[0].object, &p as &dyn Shape);
+    }
+
+    #[test]
+    fn intersecting_transformed_plane() {
+        let mut p = Plane::default();
+        p.set_transform(Matrix4::translate(0., 0., 5.) * Matrix4::rotation_x(PI / 2.));
+        let r = Ray {
+            origin: point!(0., 0., 0.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = p.intersect(r);
+        assert_eq!(xs.0.len(), 1);
+        assert!(equal(xs.0[0].t, 5.));
+        test_point!(p.normal_at(r.position(xs.0[0].t)), vector!(0., 0., 1.));
+    }
+
+    #[test]
+    fn hit_picks_plane_in_front_of_sphere() {
+        let mut floor = Plane::default();
+        floor.set_transform(Matrix4::translate(0., -1., 0.));
+        let s = Sphere::default();
+        let r = Ray {
+            origin: point!(0., 5., 0.),
+            direction: vector!(0., -1., 0.),
+        };
+        let mut xs = s.intersect(r);
+        xs.0.extend(floor.intersect(r).0);
+        let hit = xs.hit().unwrap();
+        assert_eq!(hit.t, 4.);
+        assert_eq!(hit.object, &s as &dyn Shape);
+
+        let r = Ray {
+            origin: point!(2., 5., 0.),
+            direction: vector!(0., -1., 0.),
+        };
+        let xs = floor.intersect(r);
+        assert_eq!(xs.hit().unwrap().t, 6.);
+    }
+}
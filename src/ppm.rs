@@ -0,0 +1,225 @@
+use std::{fmt::Display, fs, io, path::Path};
+
+use crate::{Canvas, Color};
+
+/// Why a PPM image couldn't be read.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PpmError {
+    pub message: String,
+}
+
+impl Display for PpmError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid PPM: {}", self.message)
+    }
+}
+
+impl std::error::Error for PpmError {}
+
+fn error(message: impl Into<String>) -> PpmError {
+    PpmError {
+        message: message.into(),
+    }
+}
+
+/// Reads the whitespace-separated header and, for plain PPMs, pixel values, skipping
+/// `#` comments.
+struct Tokens<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn next_token(&mut self) -> Option<&'a [u8]> {
+        loop {
+            match self.bytes.get(self.position)? {
+                b'#' => {
+                    while self.bytes.get(self.position).is_some_and(|&b| b != b'\n') {
+                        self.position += 1;
+                    }
+                }
+                b if b.is_ascii_whitespace() => self.position += 1,
+                _ => break,
+            }
+        }
+
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|b| !b.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+        Some(&self.bytes[start..self.position])
+    }
+
+    fn number(&mut self, what: &str) -> Result<usize, PpmError> {
+        let token = self
+            .next_token()
+            .ok_or_else(|| error(format!("missing {what}")))?;
+        std::str::from_utf8(token)
+            .ok()
+            .and_then(|t| t.parse().ok())
+            .ok_or_else(|| error(format!("bad {what}")))
+    }
+}
+
+impl Canvas {
+    /// Parses a plain (`P3`) or binary (`P6`) PPM image, scaling channels to `0.0..=1.0`.
+    pub fn from_ppm(bytes: &[u8]) -> Result<Self, PpmError> {
+        let mut tokens = Tokens { bytes, position: 0 };
+        let binary = match tokens.next_token() {
+            Some(b"P3") => false,
+            Some(b"P6") => true,
+            _ => return Err(error("expected a P3 or P6 header")),
+        };
+
+        let width = tokens.number("width")?;
+        let height = tokens.number("height")?;
+        if width == 0 || height == 0 {
+            return Err(error("image has no pixels"));
+        }
+        let max = tokens.number("maximum color value")?;
+        if max == 0 || max > 65535 {
+            return Err(error("maximum color value must be between 1 and 65535"));
+        }
+
+        let count = width
+            .checked_mul(height)
+            .and_then(|pixels| pixels.checked_mul(3))
+            .ok_or_else(|| error("image is too large"))?;
+        // every value takes at least one byte, so more than that can't be present
+        if count > bytes.len() {
+            return Err(error("pixel data is cut short"));
+        }
+
+        let values: Vec<usize> = if binary {
+            // exactly one whitespace byte separates the header from the raster
+            let start = tokens.position + 1;
+            let size = if max < 256 { 1 } else { 2 };
+            let raster = start
+                .checked_add(count * size)
+                .and_then(|end| bytes.get(start..end))
+                .ok_or_else(|| error("pixel data is cut short"))?;
+            raster
+                .chunks(size)
+                .map(|c| c.iter().fold(0, |v, &b| v * 256 + b as usize))
+                .collect()
+        } else {
+            (0..count)
+                .map(|_| tokens.number("pixel value"))
+                .collect::<Result<_, _>>()?
+        };
+        if values.iter().any(|&v| v > max) {
+            return Err(error("pixel value is above the maximum"));
+        }
+
+        let mut canvas = Canvas::new(width, height);
+        let scale = max as f64;
+        for (i, rgb) in values.chunks(3).enumerate() {
+            canvas.write_pixel(
+                i % width,
+                i / width,
+                Color::new(
+                    rgb[0] as f64 / scale,
+                    rgb[1] as f64 / scale,
+                    rgb[2] as f64 / scale,
+                ),
+            );
+        }
+        Ok(canvas)
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let bytes = fs::read(path)?;
+        Self::from_ppm(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Canvas, Color};
+
+    #[test]
+    fn reading_a_file_with_wrong_magic_number() {
+        let ppm = b"P32\n1 1\n255\n0 0 0\n";
+        assert!(Canvas::from_ppm(ppm).is_err());
+    }
+
+    #[test]
+    fn reading_a_plain_ppm_returns_a_canvas() {
+        let ppm = b"P3\n10 2\n255\n\
+            0 0 0  1 1 1  2 2 2  3 3 3  4 4 4  5 5 5  6 6 6  7 7 7  8 8 8  9 9 9\n\
+            0 0 0  1 1 1  2 2 2  3 3 3  4 4 4  5 5 5  6 6 6  7 7 7  8 8 8  9 9 9\n";
+        let c = Canvas::from_ppm(ppm).unwrap();
+        assert_eq!(c.width(), 10);
+        assert_eq!(c.height(), 2);
+        assert_eq!(
+            c.pixel_at(9, 1),
+            Color::new(9. / 255., 9. / 255., 9. / 255.)
+        );
+    }
+
+    #[test]
+    fn reading_pixel_data_from_a_ppm() {
+        let ppm = b"P3\n4 3\n255\n\
+            255 127 0  0 127 255  127 255 0  255 255 255\n\
+            0 0 0  255 0 0  0 255 0  0 0 255\n\
+            255 255 0  0 255 255  255 0 255  127 127 127\n";
+        let c = Canvas::from_ppm(ppm).unwrap();
+        let cases = [
+            (0, 0, Color::new(1., 127. / 255., 0.)),
+            (3, 0, Color::new(1., 1., 1.)),
+            (1, 1, Color::new(1., 0., 0.)),
+            (3, 2, Color::new(127. / 255., 127. / 255., 127. / 255.)),
+        ];
+        for (x, y, color) in cases {
+            assert_eq!(c.pixel_at(x, y), color);
+        }
+    }
+
+    #[test]
+    fn ppm_parsing_ignores_comments_and_scales_by_maximum() {
+        let ppm =
+            b"P3\n# this is a comment\n2 1\n# another\n100\n100 100 100  # trailing\n0 50 100\n";
+        let c = Canvas::from_ppm(ppm).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Color::new(1., 1., 1.));
+        assert_eq!(c.pixel_at(1, 0), Color::new(0., 0.5, 1.));
+    }
+
+    #[test]
+    fn reading_a_binary_ppm() {
+        let mut ppm = b"P6\n2 1\n255\n".to_vec();
+        ppm.extend([255, 0, 0, 0, 51, 255]);
+        let c = Canvas::from_ppm(&ppm).unwrap();
+        assert_eq!(c.pixel_at(0, 0), Color::new(1., 0., 0.));
+        assert_eq!(c.pixel_at(1, 0), Color::new(0., 0.2, 1.));
+    }
+
+    #[test]
+    fn truncated_ppm_is_an_error() {
+        assert!(Canvas::from_ppm(b"P3\n2 2\n255\n0 0 0\n").is_err());
+        assert!(Canvas::from_ppm(b"P6\n2 2\n255\n\0\0\0").is_err());
+    }
+
+    #[test]
+    fn oversized_dimensions_are_an_error() {
+        assert!(Canvas::from_ppm(b"P3\n99999999999 99999999999\n255\n").is_err());
+        assert!(Canvas::from_ppm(b"P6\n100000 100000\n255\n\0\0\0").is_err());
+    }
+
+    #[test]
+    fn empty_images_are_an_error() {
+        assert!(Canvas::from_ppm(b"P3 0 0 255").is_err());
+        assert!(Canvas::from_ppm(b"P6\n0 4\n255\n").is_err());
+    }
+
+    #[test]
+    fn values_above_the_maximum_are_an_error() {
+        assert!(Canvas::from_ppm(b"P3\n1 1\n100\n0 101 0\n").is_err());
+        let mut ppm = b"P6\n1 1\n15\n".to_vec();
+        ppm.extend([0, 16, 0]);
+        assert!(Canvas::from_ppm(&ppm).is_err());
+    }
+}
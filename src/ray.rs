@@ -22,7 +22,7 @@ impl Ray {
 
 #[cfg(test)]
 mod tests {
-    use crate::{matrix::Matrix4, point, sphere::Sphere, tuple::Tuple, vector};
+    use crate::{matrix::Matrix4, point, shape::Shape, sphere::Sphere, tuple::Tuple, vector};
 
     use super::Ray;
 
@@ -131,7 +131,7 @@ mod tests {
     #[test]
     fn sphere_default_transformation() {
         let s = Sphere::default();
-        assert_eq!(s.transform, Matrix4::identity());
+        assert_eq!(s.transform(), Matrix4::identity());
     }
 
     #[test]
@@ -139,7 +139,7 @@ mod tests {
         let mut s = Sphere::default();
         let t = Matrix4::translate(2., 3., 4.);
         s.set_transform(t);
-        assert_eq!(s.transform, t);
+        assert_eq!(s.transform(), t);
     }
 
     #[test]
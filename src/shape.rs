@@ -0,0 +1,279 @@
+use std::fmt::Debug;
+
+use crate::{
+    bounds::BoundingBox,
+    intersection::{Intersection, Intersections},
+    material::Material,
+    matrix::Matrix4,
+    ray::Ray,
+    transform::{ParentTransform, Transform},
+    tuple::Tuple,
+};
+
+/// Required to reach a shape's transform mutably. Only this crate can make one, so code
+/// outside it can implement [`Shape::transformation_mut`] but must go through
+/// `set_transform`, which groups override to move their children too.
+#[derive(Debug, Clone, Copy)]
+pub struct TransformAccess(());
+
+impl TransformAccess {
+    pub(crate) fn new() -> Self {
+        Self(())
+    }
+}
+
+/// Anything that can be placed in a world. Implementors only deal with object space;
+/// moving rays and normals between world and object space is done here once.
+pub trait Shape: Debug + Send + Sync {
+    /// Intersections of a ray already transformed into object space.
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_>;
+
+    /// The surface normal at a point already transformed into object space.
+    fn local_normal_at(&self, point: Tuple) -> Tuple;
+
+    /// The surface normal at the point where `hit` struck the shape. Shapes that
+    /// interpolate normals from the hit's `uv` override this.
+    fn local_normal_at_hit(&self, point: Tuple, _hit: &Intersection) -> Tuple {
+        self.local_normal_at(point)
+    }
+
+    /// The shape's transform along with its cached inverses.
+    fn transformation(&self) -> &Transform;
+
+    /// Only reachable from inside the crate; see [`TransformAccess`].
+    fn transformation_mut(&mut self, access: TransformAccess) -> &mut Transform;
+
+    fn transform(&self) -> Matrix4 {
+        self.transformation().matrix()
+    }
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.transformation_mut(TransformAccess::new())
+            .set(transform);
+    }
+
+    fn material(&self) -> &Material;
+
+    fn material_mut(&mut self) -> &mut Material;
+
+    /// The combined transform of every group above this shape; identity at the top level.
+    fn parent_transform(&self) -> Matrix4 {
+        self.transformation().parent()
+    }
+
+    /// Called by a group whenever its own place in the world changes.
+    fn set_parent_transform(&mut self, parent: ParentTransform) {
+        self.transformation_mut(TransformAccess::new())
+            .set_parent(parent);
+    }
+
+    /// Extent of the shape in object space.
+    fn bounds(&self) -> BoundingBox;
+
+    /// The bounding box as seen from the space of the group containing this shape.
+    fn parent_space_bounds(&self) -> BoundingBox {
+        self.bounds().transform(self.transform())
+    }
+
+    fn intersect(&self, ray: Ray) -> Intersections<'_> {
+        let mut ray = ray;
+        let local_ray = ray.transform(self.transformation().inverse());
+        self.local_intersect(local_ray)
+    }
+
+    /// Whether `other` is this shape or, for composite shapes, one of its descendants.
+    fn includes(&self, other: &dyn Shape) -> bool {
+        std::ptr::addr_eq(self as *const Self, other as *const dyn Shape)
+    }
+
+    fn world_to_object(&self, point: Tuple) -> Tuple {
+        self.transformation().world_inverse() * point
+    }
+
+    fn normal_to_world(&self, normal: Tuple) -> Tuple {
+        let mut world_normal = self.transformation().world_inverse_transpose() * normal;
+        world_normal.w = 0.;
+        world_normal.normalize()
+    }
+
+    fn normal_at(&self, world_point: Tuple) -> Tuple {
+        let local_point = self.world_to_object(world_point);
+        let local_normal = self.local_normal_at(local_point);
+        self.normal_to_world(self.bump(local_point, local_normal))
+    }
+
+    fn normal_at_hit(&self, world_point: Tuple, hit: &Intersection) -> Tuple {
+        let local_point = self.world_to_object(world_point);
+        let local_normal = self.local_normal_at_hit(local_point, hit);
+        self.normal_to_world(self.bump(local_point, local_normal))
+    }
+
+    /// Applies the material's bump map, if any, to an object-space normal.
+    fn bump(&self, local_point: Tuple, local_normal: Tuple) -> Tuple {
+        match &self.material().bump {
+            Some(bump) => bump.perturb(local_point, local_normal),
+            None => local_normal,
+        }
+    }
+}
+
+/// Shapes are compared by identity: two references are equal when they point at the same object.
+impl PartialEq for dyn Shape + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        f64::consts::{FRAC_1_SQRT_2, PI},
+        sync::Mutex,
+    };
+
+    use crate::{
+        bounds::BoundingBox, equal, intersection::Intersections, material::Material,
+        matrix::Matrix4, noise::BumpMap, point, ray::Ray, test_point, transform::Transform,
+        tuple::Tuple, vector,
+    };
+
+    use super::{Shape, TransformAccess};
+
+    #[derive(Debug)]
+    struct TestShape {
+        transform: Transform,
+        material: Material,
+        saved_ray: Mutex<Option<Ray>>,
+    }
+
+    impl TestShape {
+        fn new() -> Self {
+            Self {
+                transform: Transform::default(),
+                material: Material::default(),
+                saved_ray: Mutex::new(None),
+            }
+        }
+    }
+
+    impl Shape for TestShape {
+        fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+            *self.saved_ray.lock().unwrap() = Some(ray);
+            Intersections(vec![])
+        }
+
+        fn local_normal_at(&self, point: Tuple) -> Tuple {
+            vector!(point.x, point.y, point.z)
+        }
+
+        fn transformation(&self) -> &Transform {
+            &self.transform
+        }
+
+        fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+            &mut self.transform
+        }
+
+        fn material(&self) -> &Material {
+            &self.material
+        }
+
+        fn material_mut(&mut self) -> &mut Material {
+            &mut self.material
+        }
+
+        fn bounds(&self) -> BoundingBox {
+            BoundingBox::new(point!(-1., -1., -1.), point!(1., 1., 1.))
+        }
+    }
+
+    #[test]
+    fn default_transformation() {
+        let s = TestShape::new();
+        assert_eq!(s.transform(), Matrix4::identity());
+    }
+
+    #[test]
+    fn assigning_a_transformation() {
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::translate(2., 3., 4.));
+        assert_eq!(s.transform(), Matrix4::translate(2., 3., 4.));
+    }
+
+    #[test]
+    fn default_material() {
+        let s = TestShape::new();
+        assert_eq!(*s.material(), Material::default());
+    }
+
+    #[test]
+    fn assigning_a_material() {
+        let mut s = TestShape::new();
+        s.material_mut().ambient = 1.;
+        assert_eq!(s.material().ambient, 1.);
+    }
+
+    #[test]
+    fn intersecting_scaled_shape_with_ray() {
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::scaling(2., 2., 2.));
+        s.intersect(r);
+        let saved = s.saved_ray.lock().unwrap().unwrap();
+        assert_eq!(saved.origin, point!(0., 0., -2.5));
+        assert_eq!(saved.direction, vector!(0., 0., 0.5));
+    }
+
+    #[test]
+    fn intersecting_translated_shape_with_ray() {
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::translate(5., 0., 0.));
+        s.intersect(r);
+        let saved = s.saved_ray.lock().unwrap().unwrap();
+        assert_eq!(saved.origin, point!(-5., 0., -5.));
+        assert_eq!(saved.direction, vector!(0., 0., 1.));
+    }
+
+    #[test]
+    fn computing_normal_on_translated_shape() {
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::translate(0., 1., 0.));
+        let n = s.normal_at(point!(0., 1. + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        test_point!(n, vector!(0., FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn computing_normal_on_transformed_shape() {
+        let mut s = TestShape::new();
+        s.set_transform(Matrix4::scaling(1., 0.5, 1.) * Matrix4::rotation_z(PI / 5.));
+        let n = s.normal_at(point!(0., 2_f64.sqrt() / 2., -2_f64.sqrt() / 2.));
+        test_point!(n, vector!(0., 0.97014, -0.24254));
+    }
+
+    #[test]
+    fn shapes_compare_by_identity() {
+        let a = TestShape::new();
+        let b = TestShape::new();
+        assert!(&a as &dyn Shape == &a as &dyn Shape);
+        assert!(&a as &dyn Shape != &b as &dyn Shape);
+    }
+
+    #[test]
+    fn bump_map_perturbs_the_normal() {
+        let mut s = TestShape::new();
+        let p = point!(0.3, 0.4, -0.866);
+        let plain = s.normal_at(p);
+        s.material_mut().bump = Some(BumpMap::new(0.5, 3., 1));
+        let bumped = s.normal_at(p);
+        assert_ne!(bumped, plain);
+        assert!(equal(bumped.magnitude(), 1.));
+        assert!(bumped.dot(plain) > 0.5);
+    }
+}
@@ -1,15 +1,18 @@
 use crate::{
+    bounds::BoundingBox,
     intersection::{Intersection, Intersections},
     material::Material,
     matrix::Matrix4,
     point,
     ray::Ray,
+    shape::{Shape, TransformAccess},
+    transform::Transform,
     tuple::Tuple,
 };
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct Sphere {
-    pub transform: Matrix4,
+    transform: Transform,
     pub material: Material,
 }
 
@@ -22,13 +25,14 @@ impl Default for Sphere {
 impl Sphere {
     fn new(transform: Matrix4) -> Self {
         Self {
-            transform,
+            transform: Transform::new(transform),
             material: Material::default(),
         }
     }
+}
 
-    pub fn intersect(&self, mut r: Ray) -> Intersections {
-        let ray = r.transform(self.transform.inverse().unwrap());
+impl Shape for Sphere {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
         let sphere_to_ray = ray.origin - point!(0., 0., 0.);
         let a = ray.direction.dot(ray.direction);
         let b = 2.0 * ray.direction.dot(sphere_to_ray);
@@ -38,38 +42,46 @@ impl Sphere {
         if discriminant < 0.0 {
             Intersections(vec![])
         } else {
-            let t1 = Intersection {
-                t: (-b - discriminant.sqrt()) / (2.0 * a),
-                object: *self,
-            };
-            let t2 = Intersection {
-                t: (-b + discriminant.sqrt()) / (2.0 * a),
-                object: *self,
-            };
+            let t1 = Intersection::new((-b - discriminant.sqrt()) / (2.0 * a), self);
+            let t2 = Intersection::new((-b + discriminant.sqrt()) / (2.0 * a), self);
 
             Intersections(vec![t1, t2])
         }
     }
 
-    pub fn set_transform(&mut self, t: Matrix4) {
-        self.transform = t;
+    fn local_normal_at(&self, point: Tuple) -> Tuple {
+        point - point!(0., 0., 0.)
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
     }
 
-    pub fn normal_at(&self, world_point: Tuple) -> Tuple {
-        let object_point = self.transform.inverse().unwrap() * world_point;
-        let object_normal = object_point - point!(0., 0., 0.);
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
 
-        let mut world_normal = self.transform.inverse().unwrap().transpose() * object_normal;
-        world_normal.w = 0.;
-        world_normal.normalize()
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(point!(-1., -1., -1.), point!(1., 1., 1.))
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use std::f64::consts::PI;
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
 
-    use crate::{equal, matrix::Matrix4, point, test_point, tuple::Tuple, vector};
+    use crate::{
+        bounds::BoundingBox, equal, material::Material, matrix::Matrix4, point, shape::Shape,
+        test_point, tuple::Tuple, vector,
+    };
 
     use super::Sphere;
 
@@ -95,8 +107,8 @@ mod tests {
     fn compute_normal_translated_sphere() {
         let mut s = Sphere::default();
         s.set_transform(Matrix4::translate(0., 1., 0.));
-        let n = s.normal_at(point!(0., 1.70711, -0.70711));
-        test_point!(n, vector!(0., 0.70711, -0.70711));
+        let n = s.normal_at(point!(0., 1. + FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+        test_point!(n, vector!(0., FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
     }
 
     #[test]
@@ -107,4 +119,30 @@ mod tests {
         let n = s.normal_at(point!(0., 2_f64.sqrt() / 2., -2_f64.sqrt() / 2.));
         test_point!(n, vector!(0., 0.97014, -0.24254));
     }
+
+    #[test]
+    fn sphere_has_default_material() {
+        let s = Sphere::default();
+        assert_eq!(*s.material(), Material::default());
+    }
+
+    #[test]
+    fn sphere_may_be_assigned_a_material() {
+        let mut s = Sphere::default();
+        let m = Material {
+            ambient: 1.,
+            ..Default::default()
+        };
+        s.material = m.clone();
+        assert_eq!(*s.material(), m);
+    }
+
+    #[test]
+    fn sphere_has_bounding_box() {
+        let s = Sphere::default();
+        assert_eq!(
+            s.bounds(),
+            BoundingBox::new(point!(-1., -1., -1.), point!(1., 1., 1.))
+        );
+    }
 }
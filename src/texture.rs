@@ -0,0 +1,430 @@
+use std::{f64::consts::PI, fmt::Debug, sync::Arc};
+
+use crate::{pattern::Pattern, transform::Transform, tuple::Tuple, Canvas, Color, BLACK};
+
+/// How a point on an object's surface is flattened to texture coordinates `(u, v)`,
+/// each in `0.0..=1.0` with `v` increasing upward.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UvMapping {
+    /// Longitude and latitude on the unit sphere.
+    Spherical,
+    /// The xz plane, repeating every unit.
+    Planar,
+    /// Around the y axis, with `v` repeating every unit of height.
+    Cylindrical,
+    /// Each face of the unit cube gets the whole texture.
+    Cube,
+}
+
+/// The faces of the unit cube, named as seen from the front (-z).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CubeFace {
+    Left,
+    Right,
+    Front,
+    Back,
+    Up,
+    Down,
+}
+
+impl CubeFace {
+    /// The face a point on the unit cube lies on: whichever axis it's furthest along.
+    pub fn of(point: Tuple) -> Self {
+        let coord = point.x.abs().max(point.y.abs()).max(point.z.abs());
+
+        if coord == point.x {
+            CubeFace::Right
+        } else if coord == -point.x {
+            CubeFace::Left
+        } else if coord == point.y {
+            CubeFace::Up
+        } else if coord == -point.y {
+            CubeFace::Down
+        } else if coord == point.z {
+            CubeFace::Front
+        } else {
+            CubeFace::Back
+        }
+    }
+}
+
+/// Maps `-1..=1` on a cube face to `0..=1`.
+fn face_coord(value: f64) -> f64 {
+    (value + 1.).rem_euclid(2.) / 2.
+}
+
+impl UvMapping {
+    pub fn map(&self, point: Tuple) -> (f64, f64) {
+        match self {
+            UvMapping::Spherical => {
+                let theta = point.x.atan2(point.z);
+                let radius = (point.x * point.x + point.y * point.y + point.z * point.z).sqrt();
+                let phi = (point.y / radius).acos();
+                let raw_u = theta / (2. * PI);
+                // flip so u increases counterclockwise when viewed from above
+                (1. - (raw_u + 0.5), 1. - phi / PI)
+            }
+            UvMapping::Planar => (point.x.rem_euclid(1.), point.z.rem_euclid(1.)),
+            UvMapping::Cylindrical => {
+                let theta = point.x.atan2(point.z);
+                let raw_u = theta / (2. * PI);
+                (1. - (raw_u + 0.5), point.y.rem_euclid(1.))
+            }
+            UvMapping::Cube => match CubeFace::of(point) {
+                CubeFace::Front => (face_coord(point.x), face_coord(point.y)),
+                CubeFace::Back => (face_coord(-point.x), face_coord(point.y)),
+                CubeFace::Left => (face_coord(point.z), face_coord(point.y)),
+                CubeFace::Right => (face_coord(-point.z), face_coord(point.y)),
+                CubeFace::Up => (face_coord(point.x), face_coord(-point.z)),
+                CubeFace::Down => (face_coord(point.x), face_coord(point.z)),
+            },
+        }
+    }
+}
+
+/// A color that varies over texture coordinates rather than space.
+pub trait UvPattern: Debug + Send + Sync {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color;
+}
+
+/// A `width`×`height` checkerboard over the unit square.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UvCheckers {
+    pub width: f64,
+    pub height: f64,
+    pub a: Color,
+    pub b: Color,
+}
+
+impl UvPattern for UvCheckers {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        let u2 = (u * self.width).floor();
+        let v2 = (v * self.height).floor();
+        if (u2 + v2).rem_euclid(2.) == 0. {
+            self.a
+        } else {
+            self.b
+        }
+    }
+}
+
+/// How an image is sampled between pixel centers.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Filter {
+    Nearest,
+    Bilinear,
+}
+
+/// What happens to coordinates outside `0.0..=1.0`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Wrap {
+    /// The image tiles.
+    Repeat,
+    /// The edge pixels stretch outward.
+    Clamp,
+}
+
+/// An image stretched over the unit square, with `v = 0` at its bottom row. An empty
+/// image is black everywhere.
+#[derive(Debug, Clone)]
+pub struct ImageTexture {
+    pub image: Arc<Canvas>,
+    pub filter: Filter,
+    pub wrap: Wrap,
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<Canvas>) -> Self {
+        Self {
+            image,
+            filter: Filter::Bilinear,
+            wrap: Wrap::Repeat,
+        }
+    }
+
+    fn wrap_coord(&self, value: f64) -> f64 {
+        match self.wrap {
+            // keep 1.0 itself so the far edge doesn't jump back to the near one
+            Wrap::Repeat if (0. ..=1.).contains(&value) => value,
+            Wrap::Repeat => value.rem_euclid(1.),
+            Wrap::Clamp => value.clamp(0., 1.),
+        }
+    }
+
+    /// The pixel at a possibly out-of-range column or row.
+    fn texel(&self, x: isize, y: isize) -> Color {
+        let (w, h) = (self.image.width() as isize, self.image.height() as isize);
+        let (x, y) = match self.wrap {
+            Wrap::Repeat => (x.rem_euclid(w), y.rem_euclid(h)),
+            Wrap::Clamp => (x.clamp(0, w - 1), y.clamp(0, h - 1)),
+        };
+        self.image.pixel_at(x as usize, y as usize)
+    }
+}
+
+impl UvPattern for ImageTexture {
+    fn uv_pattern_at(&self, u: f64, v: f64) -> Color {
+        if self.image.width() == 0 || self.image.height() == 0 {
+            return BLACK;
+        }
+        let u = self.wrap_coord(u);
+        let v = 1. - self.wrap_coord(v);
+        // pixel coordinates, with pixel centers on whole numbers
+        let x = u * (self.image.width() - 1) as f64;
+        let y = v * (self.image.height() - 1) as f64;
+
+        match self.filter {
+            Filter::Nearest => self.texel(x.round() as isize, y.round() as isize),
+            Filter::Bilinear => {
+                let (x0, y0) = (x.floor(), y.floor());
+                let (fx, fy) = (x - x0, y - y0);
+                let (x0, y0) = (x0 as isize, y0 as isize);
+                let top = self.texel(x0, y0) * (1. - fx) + self.texel(x0 + 1, y0) * fx;
+                let bottom = self.texel(x0, y0 + 1) * (1. - fx) + self.texel(x0 + 1, y0 + 1) * fx;
+                top * (1. - fy) + bottom * fy
+            }
+        }
+    }
+}
+
+/// A UV pattern wrapped around an object by a mapping.
+#[derive(Debug)]
+pub struct TextureMap {
+    pub uv_pattern: Box<dyn UvPattern>,
+    pub mapping: UvMapping,
+    transform: Transform,
+}
+
+impl TextureMap {
+    pub fn new(uv_pattern: impl UvPattern + 'static, mapping: UvMapping) -> Self {
+        Self {
+            uv_pattern: Box::new(uv_pattern),
+            mapping,
+            transform: Transform::default(),
+        }
+    }
+}
+
+impl Pattern for TextureMap {
+    fn pattern_at(&self, point: Tuple) -> Color {
+        let (u, v) = self.mapping.map(point);
+        self.uv_pattern.uv_pattern_at(u, v)
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self) -> &mut Transform {
+        &mut self.transform
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{f64::consts::FRAC_1_SQRT_2, sync::Arc};
+
+    use crate::{equal, pattern::Pattern, point, test_color, tuple::Tuple, Canvas, Color, BLACK};
+
+    use super::{
+        CubeFace, Filter, ImageTexture, TextureMap, UvCheckers, UvMapping, UvPattern, Wrap,
+    };
+
+    const WHITE: Color = Color {
+        red: 1.,
+        green: 1.,
+        blue: 1.,
+    };
+
+    fn assert_uv(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(equal(actual.0, expected.0), "{actual:?} != {expected:?}");
+        assert!(equal(actual.1, expected.1), "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn checker_pattern_in_2d() {
+        let checkers = UvCheckers {
+            width: 2.,
+            height: 2.,
+            a: BLACK,
+            b: WHITE,
+        };
+        assert_eq!(checkers.uv_pattern_at(0., 0.), BLACK);
+        assert_eq!(checkers.uv_pattern_at(0.5, 0.), WHITE);
+        assert_eq!(checkers.uv_pattern_at(0., 0.5), WHITE);
+        assert_eq!(checkers.uv_pattern_at(0.5, 0.5), BLACK);
+        assert_eq!(checkers.uv_pattern_at(1., 1.), BLACK);
+    }
+
+    #[test]
+    fn spherical_mapping_on_a_3d_point() {
+        let cases = [
+            (point!(0., 0., -1.), (0., 0.5)),
+            (point!(1., 0., 0.), (0.25, 0.5)),
+            (point!(0., 0., 1.), (0.5, 0.5)),
+            (point!(-1., 0., 0.), (0.75, 0.5)),
+            (point!(0., 1., 0.), (0.5, 1.)),
+            (point!(0., -1., 0.), (0.5, 0.)),
+            (point!(FRAC_1_SQRT_2, FRAC_1_SQRT_2, 0.), (0.25, 0.75)),
+        ];
+        for (p, uv) in cases {
+            assert_uv(UvMapping::Spherical.map(p), uv);
+        }
+    }
+
+    #[test]
+    fn planar_mapping_on_a_3d_point() {
+        let cases = [
+            (point!(0.25, 0., 0.5), (0.25, 0.5)),
+            (point!(0.25, 0., -0.25), (0.25, 0.75)),
+            (point!(0.25, 0.5, -0.25), (0.25, 0.75)),
+            (point!(1.25, 0., 0.5), (0.25, 0.5)),
+            (point!(0.25, 0., -1.75), (0.25, 0.25)),
+            (point!(1., 0., -1.), (0., 0.)),
+            (point!(0., 0., 0.), (0., 0.)),
+        ];
+        for (p, uv) in cases {
+            assert_uv(UvMapping::Planar.map(p), uv);
+        }
+    }
+
+    #[test]
+    fn cylindrical_mapping_on_a_3d_point() {
+        let cases = [
+            (point!(0., 0., -1.), (0., 0.)),
+            (point!(0., 0.5, -1.), (0., 0.5)),
+            (point!(0., 1., -1.), (0., 0.)),
+            (point!(FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.125, 0.5)),
+            (point!(1., 0.5, 0.), (0.25, 0.5)),
+            (point!(FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.375, 0.5)),
+            (point!(0., -0.25, 1.), (0.5, 0.75)),
+            (point!(-FRAC_1_SQRT_2, 0.5, FRAC_1_SQRT_2), (0.625, 0.5)),
+            (point!(-1., 1.25, 0.), (0.75, 0.25)),
+            (point!(-FRAC_1_SQRT_2, 0.5, -FRAC_1_SQRT_2), (0.875, 0.5)),
+        ];
+        for (p, uv) in cases {
+            assert_uv(UvMapping::Cylindrical.map(p), uv);
+        }
+    }
+
+    #[test]
+    fn identifying_the_face_of_a_cube_from_a_point() {
+        let cases = [
+            (point!(-1., 0.5, -0.25), CubeFace::Left),
+            (point!(1.1, -0.75, 0.8), CubeFace::Right),
+            (point!(0.1, 0.6, 0.9), CubeFace::Front),
+            (point!(-0.7, 0., -2.), CubeFace::Back),
+            (point!(0.5, 1., 0.9), CubeFace::Up),
+            (point!(-0.2, -1.3, 1.1), CubeFace::Down),
+        ];
+        for (p, face) in cases {
+            assert_eq!(CubeFace::of(p), face);
+        }
+    }
+
+    #[test]
+    fn cube_mapping_on_each_face() {
+        let cases = [
+            (point!(-0.5, 0.5, 1.), (0.25, 0.75)),
+            (point!(0.5, -0.5, 1.), (0.75, 0.25)),
+            (point!(0.5, 0.5, -1.), (0.25, 0.75)),
+            (point!(-0.5, -0.5, -1.), (0.75, 0.25)),
+            (point!(-1., 0.5, -0.5), (0.25, 0.75)),
+            (point!(-1., -0.5, 0.5), (0.75, 0.25)),
+            (point!(1., 0.5, 0.5), (0.25, 0.75)),
+            (point!(1., -0.5, -0.5), (0.75, 0.25)),
+            (point!(-0.5, 1., -0.5), (0.25, 0.75)),
+            (point!(0.5, 1., 0.5), (0.75, 0.25)),
+            (point!(-0.5, -1., 0.5), (0.25, 0.75)),
+            (point!(0.5, -1., -0.5), (0.75, 0.25)),
+        ];
+        for (p, uv) in cases {
+            assert_uv(UvMapping::Cube.map(p), uv);
+        }
+    }
+
+    #[test]
+    fn using_a_texture_map_pattern_with_a_spherical_map() {
+        let checkers = UvCheckers {
+            width: 16.,
+            height: 8.,
+            a: BLACK,
+            b: WHITE,
+        };
+        let pattern = TextureMap::new(checkers, UvMapping::Spherical);
+        let cases = [
+            (point!(0.4315, 0.4670, 0.7719), WHITE),
+            (point!(-0.9654, 0.2552, -0.0534), BLACK),
+            (point!(0.1039, 0.7090, 0.6975), WHITE),
+            (point!(-0.4986, -0.7856, -0.3663), BLACK),
+            (point!(-0.0317, -0.9395, 0.3411), BLACK),
+            (point!(0.4809, -0.7721, 0.4154), BLACK),
+            (point!(0.0285, -0.9612, -0.2745), BLACK),
+            (point!(-0.5734, -0.2162, -0.7903), WHITE),
+            (point!(0.7688, -0.1470, 0.6223), BLACK),
+            (point!(-0.7652, 0.2175, 0.6060), BLACK),
+        ];
+        for (p, color) in cases {
+            assert_eq!(pattern.pattern_at(p), color);
+        }
+    }
+
+    /// Two by two: black and white on top, red and green below.
+    fn small_image() -> Arc<Canvas> {
+        let mut image = Canvas::new(2, 2);
+        image.write_pixel(0, 0, BLACK);
+        image.write_pixel(1, 0, WHITE);
+        image.write_pixel(0, 1, Color::new(1., 0., 0.));
+        image.write_pixel(1, 1, Color::new(0., 1., 0.));
+        Arc::new(image)
+    }
+
+    #[test]
+    fn nearest_filtering_picks_the_closest_pixel() {
+        let mut texture = ImageTexture::new(small_image());
+        texture.filter = Filter::Nearest;
+        assert_eq!(texture.uv_pattern_at(0., 1.), BLACK);
+        assert_eq!(texture.uv_pattern_at(0.9, 0.9), WHITE);
+        assert_eq!(texture.uv_pattern_at(0.1, 0.2), Color::new(1., 0., 0.));
+        assert_eq!(texture.uv_pattern_at(1., 0.), Color::new(0., 1., 0.));
+    }
+
+    #[test]
+    fn bilinear_filtering_blends_neighbouring_pixels() {
+        let texture = ImageTexture::new(small_image());
+        assert_eq!(texture.uv_pattern_at(0., 1.), BLACK);
+        test_color!(texture.uv_pattern_at(0.5, 1.), Color::new(0.5, 0.5, 0.5));
+        test_color!(texture.uv_pattern_at(0.5, 0.5), Color::new(0.5, 0.5, 0.25));
+    }
+
+    #[test]
+    fn wrap_modes_handle_coordinates_outside_the_image() {
+        let mut texture = ImageTexture::new(small_image());
+        texture.filter = Filter::Nearest;
+        assert_eq!(texture.uv_pattern_at(1.9, 0.9), WHITE);
+        texture.wrap = Wrap::Clamp;
+        assert_eq!(texture.uv_pattern_at(1.9, 0.9), WHITE);
+        assert_eq!(texture.uv_pattern_at(-3., -0.5), Color::new(1., 0., 0.));
+        texture.wrap = Wrap::Repeat;
+        assert_eq!(texture.uv_pattern_at(-0.9, 0.1), Color::new(1., 0., 0.));
+    }
+
+    #[test]
+    fn empty_image_texture_is_black() {
+        let mut texture = ImageTexture::new(Arc::new(Canvas::new(0, 0)));
+        assert_eq!(texture.uv_pattern_at(0.3, 0.7), BLACK);
+        texture.filter = Filter::Nearest;
+        texture.wrap = Wrap::Clamp;
+        assert_eq!(texture.uv_pattern_at(0.3, 0.7), BLACK);
+    }
+
+    #[test]
+    fn image_texture_on_a_sphere() {
+        let pattern = TextureMap::new(ImageTexture::new(small_image()), UvMapping::Spherical);
+        // the north pole is v = 1, the top row of the image
+        test_color!(
+            pattern.pattern_at(point!(0., 1., 0.)),
+            Color::new(0.5, 0.5, 0.5)
+        );
+    }
+}
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+
+use crate::matrix::Matrix4;
+
+/// A shape's transform together with the inverses tracing needs, computed once when the
+/// transform or the shape's place in a group changes rather than on every ray.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transform {
+    matrix: Matrix4,
+    inverse: Matrix4,
+    /// The groups above the shape, shared with its siblings; `None` at the top level.
+    parent: Option<ParentTransform>,
+    /// Takes world space points all the way down to object space.
+    world_inverse: Matrix4,
+    world_inverse_transpose: Matrix4,
+}
+
+/// The combined transform of a group and every group above it, handed to its children.
+/// Cloning it is cheap, so a mesh's triangles all share one copy of the group's matrices.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParentTransform(Arc<WorldSpace>);
+
+#[derive(Debug, PartialEq)]
+struct WorldSpace {
+    to_world: Matrix4,
+    /// Takes world space points all the way down to the group's space.
+    world_inverse: Matrix4,
+}
+
+impl ParentTransform {
+    /// Group space to world space.
+    pub fn matrix(&self) -> Matrix4 {
+        self.0.to_world
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::new(Matrix4::identity())
+    }
+}
+
+impl Transform {
+    /// Panics if `matrix` can't be inverted, since no ray could be traced through it.
+    pub fn new(matrix: Matrix4) -> Self {
+        let inverse = invert(matrix);
+        Self {
+            matrix,
+            inverse,
+            parent: None,
+            world_inverse: inverse,
+            world_inverse_transpose: inverse.transpose(),
+        }
+    }
+
+    pub fn matrix(&self) -> Matrix4 {
+        self.matrix
+    }
+
+    pub fn inverse(&self) -> Matrix4 {
+        self.inverse
+    }
+
+    /// The combined transform of every group above the shape.
+    pub fn parent(&self) -> Matrix4 {
+        self.parent
+            .as_ref()
+            .map_or(Matrix4::identity(), ParentTransform::matrix)
+    }
+
+    pub fn world_inverse(&self) -> Matrix4 {
+        self.world_inverse
+    }
+
+    pub fn world_inverse_transpose(&self) -> Matrix4 {
+        self.world_inverse_transpose
+    }
+
+    /// `parent * matrix`: object space to world space.
+    pub fn to_world(&self) -> Matrix4 {
+        self.parent() * self.matrix
+    }
+
+    /// This transform as the parent of a group's children, computed once for all of them.
+    pub fn as_parent(&self) -> ParentTransform {
+        ParentTransform(Arc::new(WorldSpace {
+            to_world: self.to_world(),
+            world_inverse: self.world_inverse,
+        }))
+    }
+
+    pub fn set(&mut self, matrix: Matrix4) {
+        self.matrix = matrix;
+        self.inverse = invert(matrix);
+        self.update_world();
+    }
+
+    /// Moves the shape under `parent`. The shape's own inverse is kept, and nothing is
+    /// inverted again.
+    pub fn set_parent(&mut self, parent: ParentTransform) {
+        self.parent = Some(parent);
+        self.update_world();
+    }
+
+    fn update_world(&mut self) {
+        self.world_inverse = match &self.parent {
+            Some(parent) => self.inverse * parent.0.world_inverse,
+            None => self.inverse,
+        };
+        self.world_inverse_transpose = self.world_inverse.transpose();
+    }
+}
+
+fn invert(matrix: Matrix4) -> Matrix4 {
+    matrix
+        .inverse()
+        .expect("shape transforms must be invertible")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{equal, matrix::Matrix4};
+
+    use super::Transform;
+
+    #[test]
+    fn default_transform_is_identity() {
+        let t = Transform::default();
+        assert_eq!(t.matrix(), Matrix4::identity());
+        assert_eq!(t.inverse(), Matrix4::identity());
+        assert_eq!(t.world_inverse_transpose(), Matrix4::identity());
+    }
+
+    #[test]
+    fn setting_a_transform_caches_its_inverse() {
+        let m = Matrix4::translate(1., 2., 3.) * Matrix4::scaling(2., 2., 2.);
+        let mut t = Transform::default();
+        t.set(m);
+        assert_eq!(t.matrix(), m);
+        assert_eq!(t.inverse(), m.inverse().unwrap());
+        assert_eq!(t.world_inverse(), m.inverse().unwrap());
+        assert_eq!(
+            t.world_inverse_transpose(),
+            m.inverse().unwrap().transpose()
+        );
+    }
+
+    #[test]
+    fn setting_a_parent_updates_the_world_inverse() {
+        let m = Matrix4::scaling(1., 2., 3.);
+        let parent = Matrix4::translate(0., 5., 0.);
+        let mut t = Transform::new(m);
+        t.set_parent(Transform::new(parent).as_parent());
+        assert_eq!(t.inverse(), m.inverse().unwrap());
+        assert_eq!(t.parent(), parent);
+        assert_eq!(t.world_inverse(), (parent * m).inverse().unwrap());
+        assert_eq!(t.to_world(), parent * m);
+    }
+
+    #[test]
+    fn nested_parents_combine() {
+        let outer = Transform::new(Matrix4::rotation_y(0.5));
+        let mut inner = Transform::new(Matrix4::scaling(2., 2., 2.));
+        inner.set_parent(outer.as_parent());
+        let mut t = Transform::new(Matrix4::translate(5., 0., 0.));
+        t.set_parent(inner.as_parent());
+        let to_world = Matrix4::rotation_y(0.5)
+            * Matrix4::scaling(2., 2., 2.)
+            * Matrix4::translate(5., 0., 0.);
+        assert_eq!(t.to_world(), to_world);
+        let expected = to_world.inverse().unwrap();
+        let actual = t.world_inverse();
+        for row in 0..4 {
+            for col in 0..4 {
+                assert!(equal(actual.0[row][col], expected.0[row][col]));
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "invertible")]
+    fn non_invertible_transforms_are_rejected() {
+        Transform::new(Matrix4::scaling(0., 1., 1.));
+    }
+}
@@ -0,0 +1,323 @@
+use crate::{
+    bounds::BoundingBox,
+    intersection::{Intersection, Intersections},
+    material::Material,
+    ray::Ray,
+    shape::{Shape, TransformAccess},
+    transform::Transform,
+    tuple::Tuple,
+    EPSILON,
+};
+
+/// A flat triangle with its vertices given in object space.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub e1: Tuple,
+    pub e2: Tuple,
+    pub normal: Tuple,
+    transform: Transform,
+    pub material: Material,
+}
+
+/// A triangle whose normal is interpolated from a normal at each vertex.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SmoothTriangle {
+    pub p1: Tuple,
+    pub p2: Tuple,
+    pub p3: Tuple,
+    pub n1: Tuple,
+    pub n2: Tuple,
+    pub n3: Tuple,
+    pub e1: Tuple,
+    pub e2: Tuple,
+    transform: Transform,
+    pub material: Material,
+}
+
+/// Möller–Trumbore: the distance along the ray and the barycentric `u`/`v` of the hit.
+fn intersect_triangle(ray: Ray, p1: Tuple, e1: Tuple, e2: Tuple) -> Option<(f64, f64, f64)> {
+    let dir_cross_e2 = Tuple::cross(ray.direction, e2);
+    let det = e1.dot(dir_cross_e2);
+
+    // the ray is parallel to the triangle's plane
+    if det.abs() < EPSILON {
+        return None;
+    }
+
+    let f = 1. / det;
+    let p1_to_origin = ray.origin - p1;
+    let u = f * p1_to_origin.dot(dir_cross_e2);
+    if !(0. ..=1.).contains(&u) {
+        return None;
+    }
+
+    let origin_cross_e1 = Tuple::cross(p1_to_origin, e1);
+    let v = f * ray.direction.dot(origin_cross_e1);
+    if v < 0. || u + v > 1. {
+        return None;
+    }
+
+    Some((f * e2.dot(origin_cross_e1), u, v))
+}
+
+fn triangle_bounds(p1: Tuple, p2: Tuple, p3: Tuple) -> BoundingBox {
+    let mut b = BoundingBox::new(p1, p1);
+    b.add_point(p2);
+    b.add_point(p3);
+    b
+}
+
+impl Triangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        Self {
+            p1,
+            p2,
+            p3,
+            e1,
+            e2,
+            normal: Tuple::cross(e2, e1).normalize(),
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+}
+
+impl SmoothTriangle {
+    pub fn new(p1: Tuple, p2: Tuple, p3: Tuple, n1: Tuple, n2: Tuple, n3: Tuple) -> Self {
+        Self {
+            p1,
+            p2,
+            p3,
+            n1,
+            n2,
+            n3,
+            e1: p2 - p1,
+            e2: p3 - p1,
+            transform: Transform::default(),
+            material: Material::default(),
+        }
+    }
+}
+
+impl Shape for Triangle {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+        match intersect_triangle(ray, self.p1, self.e1, self.e2) {
+            Some((t, u, v)) => Intersections(vec![Intersection::with_uv(t, self, u, v)]),
+            None => Intersections(vec![]),
+        }
+    }
+
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        self.normal
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(self.p1, self.p2, self.p3)
+    }
+}
+
+impl Shape for SmoothTriangle {
+    fn local_intersect(&self, ray: Ray) -> Intersections<'_> {
+        match intersect_triangle(ray, self.p1, self.e1, self.e2) {
+            Some((t, u, v)) => Intersections(vec![Intersection::with_uv(t, self, u, v)]),
+            None => Intersections(vec![]),
+        }
+    }
+
+    /// Without a hit to interpolate from, the face normal.
+    fn local_normal_at(&self, _point: Tuple) -> Tuple {
+        Tuple::cross(self.e2, self.e1).normalize()
+    }
+
+    fn local_normal_at_hit(&self, point: Tuple, hit: &Intersection) -> Tuple {
+        match hit.uv {
+            Some((u, v)) => self.n2 * u + self.n3 * v + self.n1 * (1. - u - v),
+            None => self.local_normal_at(point),
+        }
+    }
+
+    fn transformation(&self) -> &Transform {
+        &self.transform
+    }
+
+    fn transformation_mut(&mut self, _: TransformAccess) -> &mut Transform {
+        &mut self.transform
+    }
+
+    fn material(&self) -> &Material {
+        &self.material
+    }
+
+    fn material_mut(&mut self) -> &mut Material {
+        &mut self.material
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        triangle_bounds(self.p1, self.p2, self.p3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        bounds::BoundingBox,
+        equal,
+        intersection::{Intersection, Intersections},
+        point,
+        ray::Ray,
+        shape::Shape,
+        test_point,
+        tuple::Tuple,
+        vector,
+    };
+
+    use super::{SmoothTriangle, Triangle};
+
+    fn smooth_triangle() -> SmoothTriangle {
+        SmoothTriangle::new(
+            point!(0., 1., 0.),
+            point!(-1., 0., 0.),
+            point!(1., 0., 0.),
+            vector!(0., 1., 0.),
+            vector!(-1., 0., 0.),
+            vector!(1., 0., 0.),
+        )
+    }
+
+    #[test]
+    fn constructing_a_triangle() {
+        let p1 = point!(0., 1., 0.);
+        let p2 = point!(-1., 0., 0.);
+        let p3 = point!(1., 0., 0.);
+        let t = Triangle::new(p1, p2, p3);
+        assert_eq!(t.p1, p1);
+        assert_eq!(t.p2, p2);
+        assert_eq!(t.p3, p3);
+        assert_eq!(t.e1, vector!(-1., -1., 0.));
+        assert_eq!(t.e2, vector!(1., -1., 0.));
+        assert_eq!(t.normal, vector!(0., 0., -1.));
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_triangle() {
+        let t = Triangle::new(point!(0., 1., 0.), point!(-1., 0., 0.), point!(1., 0., 0.));
+        assert_eq!(t.local_normal_at(point!(0., 0.5, 0.)), t.normal);
+        assert_eq!(t.local_normal_at(point!(-0.5, 0.75, 0.)), t.normal);
+        assert_eq!(t.local_normal_at(point!(0.5, 0.25, 0.)), t.normal);
+    }
+
+    #[test]
+    fn intersecting_ray_parallel_to_triangle() {
+        let t = Triangle::new(point!(0., 1., 0.), point!(-1., 0., 0.), point!(1., 0., 0.));
+        let r = Ray {
+            origin: point!(0., -1., -2.),
+            direction: vector!(0., 1., 0.),
+        };
+        assert!(t.local_intersect(r).0.is_empty());
+    }
+
+    #[test]
+    fn ray_misses_the_triangle_edges() {
+        let t = Triangle::new(point!(0., 1., 0.), point!(-1., 0., 0.), point!(1., 0., 0.));
+        for origin in [
+            point!(1., 1., -2.),
+            point!(-1., 1., -2.),
+            point!(0., -1., -2.),
+        ] {
+            let r = Ray {
+                origin,
+                direction: vector!(0., 0., 1.),
+            };
+            assert!(t.local_intersect(r).0.is_empty());
+        }
+    }
+
+    #[test]
+    fn ray_strikes_a_triangle() {
+        let t = Triangle::new(point!(0., 1., 0.), point!(-1., 0., 0.), point!(1., 0., 0.));
+        let r = Ray {
+            origin: point!(0., 0.5, -2.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = t.local_intersect(r);
+        assert_eq!(xs.0.len(), 1);
+        assert_eq!(xs.0[0].t, 2.);
+    }
+
+    #[test]
+    fn bounding_box_of_a_triangle() {
+        let t = Triangle::new(
+            point!(-3., 7., 2.),
+            point!(6., 2., -4.),
+            point!(2., -1., -1.),
+        );
+        assert_eq!(
+            t.bounds(),
+            BoundingBox::new(point!(-3., -1., -4.), point!(6., 7., 2.))
+        );
+    }
+
+    #[test]
+    fn constructing_a_smooth_triangle() {
+        let tri = smooth_triangle();
+        assert_eq!(tri.p1, point!(0., 1., 0.));
+        assert_eq!(tri.n1, vector!(0., 1., 0.));
+        assert_eq!(tri.n3, vector!(1., 0., 0.));
+    }
+
+    #[test]
+    fn intersection_with_smooth_triangle_stores_u_v() {
+        let tri = smooth_triangle();
+        let r = Ray {
+            origin: point!(-0.2, 0.3, -2.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = tri.local_intersect(r);
+        let (u, v) = xs.0[0].uv.unwrap();
+        assert!(equal(u, 0.45));
+        assert!(equal(v, 0.25));
+    }
+
+    #[test]
+    fn smooth_triangle_uses_u_v_to_interpolate_the_normal() {
+        let tri = smooth_triangle();
+        let i = Intersection::with_uv(1., &tri, 0.45, 0.25);
+        let n = tri.normal_at_hit(point!(0., 0., 0.), &i);
+        test_point!(n, vector!(-0.5547, 0.83205, 0.));
+    }
+
+    #[test]
+    fn preparing_the_normal_on_a_smooth_triangle() {
+        let tri = smooth_triangle();
+        let i = Intersection::with_uv(1., &tri, 0.45, 0.25);
+        let r = Ray {
+            origin: point!(-0.2, 0.3, -2.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = Intersections(vec![i]);
+        let comps = xs.0[0].prepare_computations(r, &xs);
+        test_point!(comps.normalv, vector!(-0.5547, 0.83205, 0.));
+    }
+}
@@ -71,7 +71,7 @@ impl Tuple {
         (self.x * b.x) + (self.y * b.y) + (self.z * b.z) + (self.w * b.w)
     }
 
-    fn cross(a: Tuple, b: Tuple) -> Tuple {
+    pub fn cross(a: Tuple, b: Tuple) -> Tuple {
         vector!(
             a.y * b.z - a.z * b.y,
             a.z * b.x - a.x * b.z,
@@ -145,8 +145,8 @@ impl Div<f64> for Tuple {
 
 #[cfg(test)]
 mod tests {
+    use crate::equal;
     use crate::tuple::Tuple;
-    use crate::{equal, test_point};
 
     #[test]
     fn test_magnitude() {
@@ -0,0 +1,840 @@
+use std::sync::OnceLock;
+
+use crate::{
+    bvh::{Bvh, BvhStats},
+    environment::EnvironmentLight,
+    intersection::{Computations, Intersections},
+    light::{Light, LightSample, PointLight},
+    matrix::Matrix4,
+    point,
+    ray::Ray,
+    shape::Shape,
+    sphere::Sphere,
+    tuple::Tuple,
+    Color, BLACK,
+};
+
+pub const DEFAULT_MAX_DEPTH: usize = 5;
+
+/// A scene: every object that can be hit and every light that shades it.
+#[derive(Debug)]
+pub struct World {
+    objects: Vec<Box<dyn Shape>>,
+    pub lights: Vec<Box<dyn Light>>,
+    /// What rays that miss everything see, instead of black. It also lights the scene
+    /// alongside `lights`, so it shouldn't be added there too.
+    pub environment: Option<EnvironmentLight>,
+    /// How many times a ray may bounce before it stops picking up reflections, so facing
+    /// mirrors don't recurse forever.
+    pub max_depth: usize,
+    /// Built on the first ray and dropped whenever an object is added or changed.
+    bvh: OnceLock<Bvh>,
+    record_bvh_stats: bool,
+}
+
+impl Default for World {
+    /// The two concentric spheres lit from the upper left used throughout the tests.
+    fn default() -> Self {
+        let light = PointLight::new(point!(-10., 10., -10.), Color::new(1., 1., 1.));
+
+        let mut s1 = Sphere::default();
+        s1.material.color = Color::new(0.8, 1.0, 0.6);
+        s1.material.diffuse = 0.7;
+        s1.material.specular = 0.2;
+
+        let mut s2 = Sphere::default();
+        s2.set_transform(Matrix4::scaling(0.5, 0.5, 0.5));
+
+        Self {
+            objects: vec![Box::new(s1), Box::new(s2)],
+            lights: vec![Box::new(light)],
+            environment: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            bvh: OnceLock::new(),
+            record_bvh_stats: false,
+        }
+    }
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self {
+            objects: vec![],
+            lights: vec![],
+            environment: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            bvh: OnceLock::new(),
+            record_bvh_stats: false,
+        }
+    }
+
+    pub fn objects(&self) -> &[Box<dyn Shape>] {
+        &self.objects
+    }
+
+    /// Mutable access to one object. The hierarchy is rebuilt on the next ray, since the
+    /// object may be moved.
+    pub fn object_mut(&mut self, index: usize) -> &mut dyn Shape {
+        self.bvh.take();
+        self.objects[index].as_mut()
+    }
+
+    pub fn add_object(&mut self, object: impl Shape + 'static) {
+        self.objects.push(Box::new(object));
+        self.bvh.take();
+    }
+
+    pub fn add_light(&mut self, light: impl Light + 'static) {
+        self.lights.push(Box::new(light));
+    }
+
+    /// Surrounds the scene with `environment`, both as the background and as a light,
+    /// replacing any previous one.
+    pub fn set_environment(&mut self, environment: EnvironmentLight) {
+        self.environment = Some(environment);
+    }
+
+    fn bvh(&self) -> &Bvh {
+        self.bvh.get_or_init(|| {
+            let mut bvh = Bvh::build(&self.objects);
+            bvh.record_stats(self.record_bvh_stats);
+            bvh
+        })
+    }
+
+    pub fn intersect_world(&self, ray: Ray) -> Intersections<'_> {
+        self.bvh().intersect(&self.objects, ray)
+    }
+
+    /// Starts or stops counting traversal work for [`World::bvh_stats`]. Off by default,
+    /// since the counters are shared by every render thread.
+    pub fn record_bvh_stats(&mut self, record: bool) {
+        self.record_bvh_stats = record;
+        if let Some(bvh) = self.bvh.get_mut() {
+            bvh.record_stats(record);
+        }
+    }
+
+    /// Traversal counters for the rays cast into the world while stats were being recorded,
+    /// since the hierarchy was built.
+    pub fn bvh_stats(&self) -> BvhStats {
+        self.bvh().stats()
+    }
+
+    /// The color at a hit. `remaining` is how many more bounces reflected rays may take.
+    pub fn shade_hit(&self, comps: &Computations, remaining: usize) -> Color {
+        let environment = self.environment.iter().map(|e| e as &dyn Light);
+        let lights = self.lights.iter().map(|l| l.as_ref()).chain(environment);
+        let surface = lights.fold(BLACK, |color, light| {
+            color
+                + comps.object.material().lightning_samples(
+                    comps.object,
+                    light.intensity(),
+                    &self.light_samples(comps.over_point, light),
+                    comps.over_point,
+                    comps.eyev,
+                    comps.normalv,
+                )
+        });
+
+        let reflected = self.reflected_color(comps, remaining);
+        let refracted = self.refracted_color(comps, remaining);
+
+        let material = comps.object.material();
+        if material.reflective > 0. && material.transparency > 0. {
+            let reflectance = comps.schlick();
+            surface + reflected * reflectance + refracted * (1. - reflectance)
+        } else {
+            surface + reflected + refracted
+        }
+    }
+
+    pub fn reflected_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let reflective = comps.object.material().reflective;
+        if remaining == 0 || reflective == 0. {
+            return BLACK;
+        }
+
+        let reflect_ray = Ray {
+            origin: comps.over_point,
+            direction: comps.reflectv,
+        };
+        self.color_at_depth(reflect_ray, remaining - 1) * reflective
+    }
+
+    pub fn refracted_color(&self, comps: &Computations, remaining: usize) -> Color {
+        let transparency = comps.object.material().transparency;
+        if remaining == 0 || transparency == 0. {
+            return BLACK;
+        }
+
+        // Snell's law, with the angles' sines and cosines worked out from the vectors
+        let n_ratio = comps.n1 / comps.n2;
+        let cos_i = comps.eyev.dot(comps.normalv);
+        let sin2_t = n_ratio * n_ratio * (1. - cos_i * cos_i);
+        if sin2_t > 1. {
+            // total internal reflection
+            return BLACK;
+        }
+
+        let cos_t = (1. - sin2_t).sqrt();
+        let direction = comps.normalv * (n_ratio * cos_i - cos_t) - comps.eyev * n_ratio;
+        let refract_ray = Ray {
+            origin: comps.under_point,
+            direction,
+        };
+        self.color_at_depth(refract_ray, remaining - 1) * transparency
+    }
+
+    /// Whether anything blocks the light arriving at `point` from `sample`.
+    pub fn is_shadowed(&self, point: Tuple, sample: &LightSample) -> bool {
+        let ray = Ray {
+            origin: point,
+            direction: sample.direction,
+        };
+
+        matches!(self.intersect_world(ray).hit(), Some(hit) if hit.t < sample.distance)
+    }
+
+    /// Whether anything sits between `point` and `light_position`.
+    pub fn is_shadowed_from(&self, point: Tuple, light_position: Tuple) -> bool {
+        self.is_shadowed(point, &LightSample::toward(point, light_position, BLACK))
+    }
+
+    /// Where `light` reaches `point` from, each with 1.0 if the sample can be seen from
+    /// `point` and 0.0 if it's blocked.
+    pub fn light_samples(&self, point: Tuple, light: &dyn Light) -> Vec<(LightSample, f64)> {
+        light
+            .samples(point)
+            .into_iter()
+            .map(|sample| {
+                let visibility = if self.is_shadowed(point, &sample) {
+                    0.
+                } else {
+                    1.
+                };
+                (sample, visibility)
+            })
+            .collect()
+    }
+
+    pub fn color_at(&self, ray: Ray) -> Color {
+        self.color_at_depth(ray, self.max_depth)
+    }
+
+    pub fn color_at_depth(&self, ray: Ray, remaining: usize) -> Color {
+        let xs = self.intersect_world(ray);
+
+        match xs.hit() {
+            Some(hit) => self.shade_hit(&hit.prepare_computations(ray, &xs), remaining),
+            None => match &self.environment {
+                Some(environment) => environment.color_in(ray.direction),
+                None => BLACK,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI, SQRT_2};
+
+    use crate::{
+        environment::EnvironmentLight,
+        equal,
+        intersection::{Intersection, Intersections},
+        light::{AreaLight, DirectionalLight, Light, PointLight, SpotLight},
+        material::Material,
+        matrix::Matrix4,
+        plane::Plane,
+        point,
+        ray::Ray,
+        shape::Shape,
+        sphere::Sphere,
+        test_color,
+        tuple::Tuple,
+        vector, Canvas, Color,
+    };
+
+    use super::{World, DEFAULT_MAX_DEPTH};
+
+    #[test]
+    fn creating_a_world() {
+        let w = World::new();
+        assert!(w.objects().is_empty());
+        assert!(w.lights.is_empty());
+    }
+
+    #[test]
+    fn the_default_world() {
+        let light = PointLight::new(point!(-10., 10., -10.), Color::new(1., 1., 1.));
+        let mut s1 = Sphere::default();
+        s1.material.color = Color::new(0.8, 1.0, 0.6);
+        s1.material.diffuse = 0.7;
+        s1.material.specular = 0.2;
+        let mut s2 = Sphere::default();
+        s2.set_transform(Matrix4::scaling(0.5, 0.5, 0.5));
+
+        let w = World::default();
+        assert_eq!(w.lights.len(), 1);
+        assert_eq!(
+            w.lights[0].samples(point!(0., 0., 0.)),
+            light.samples(point!(0., 0., 0.))
+        );
+        assert_eq!(w.objects().len(), 2);
+        assert_eq!(w.objects()[0].transform(), s1.transform());
+        assert_eq!(*w.objects()[0].material(), s1.material);
+        assert_eq!(w.objects()[1].transform(), s2.transform());
+        assert_eq!(*w.objects()[1].material(), s2.material);
+    }
+
+    #[test]
+    fn intersect_world_with_ray() {
+        let w = World::default();
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = w.intersect_world(r);
+        assert_eq!(xs.0.len(), 4);
+        assert_eq!(xs.0[0].t, 4.);
+        assert_eq!(xs.0[1].t, 4.5);
+        assert_eq!(xs.0[2].t, 5.5);
+        assert_eq!(xs.0[3].t, 6.);
+    }
+
+    #[test]
+    fn shading_an_intersection() {
+        let w = World::default();
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let i = Intersection::new(4., w.objects()[0].as_ref());
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        let c = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+        test_color!(c, Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn shading_an_intersection_from_the_inside() {
+        let mut w = World::default();
+        w.lights = vec![Box::new(PointLight::new(
+            point!(0., 0.25, 0.),
+            Color::new(1., 1., 1.),
+        ))];
+        let r = Ray {
+            origin: point!(0., 0., 0.),
+            direction: vector!(0., 0., 1.),
+        };
+        let i = Intersection::new(0.5, w.objects()[1].as_ref());
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        let c = w.shade_hit(&comps, DEFAULT_MAX_DEPTH);
+        test_color!(c, Color::new(0.90498, 0.90498, 0.90498));
+    }
+
+    #[test]
+    fn shading_with_multiple_lights_adds_contributions() {
+        let mut w = World::default();
+        w.add_light(PointLight::new(
+            point!(-10., 10., -10.),
+            Color::new(1., 1., 1.),
+        ));
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let c = w.color_at(r);
+        test_color!(c, Color::new(0.76132, 0.95166, 0.5710));
+    }
+
+    #[test]
+    fn color_when_ray_misses() {
+        let w = World::default();
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 1., 0.),
+        };
+        assert_eq!(w.color_at(r), Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn color_when_ray_misses_shows_the_environment() {
+        let mut w = World::default();
+        let mut sky = Canvas::new(4, 2);
+        for x in 0..4 {
+            sky.write_pixel(x, 0, Color::new(0.5, 0.7, 1.));
+            sky.write_pixel(x, 1, Color::new(0.3, 0.2, 0.1));
+        }
+        w.set_environment(EnvironmentLight::new(sky, 16));
+        assert_eq!(w.lights.len(), 1);
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 1., 0.),
+        };
+        test_color!(w.color_at(r), Color::new(0.5, 0.7, 1.));
+    }
+
+    #[test]
+    fn environment_lights_objects_from_every_side() {
+        let mut w = World::new();
+        let mut ball = Sphere::default();
+        ball.material.ambient = 0.;
+        ball.material.specular = 0.;
+        ball.material.diffuse = 1.;
+        w.add_object(ball);
+        let mut white = Canvas::new(16, 8);
+        for y in 0..8 {
+            for x in 0..16 {
+                white.write_pixel(x, y, Color::new(1., 1., 1.));
+            }
+        }
+        w.set_environment(EnvironmentLight::new(white, 256));
+
+        for direction in [
+            vector!(0., 0., 1.),
+            vector!(0., -1., 0.),
+            vector!(1., 0., 0.),
+        ] {
+            let r = Ray {
+                origin: point!(0., 0., 0.) + direction * -5.,
+                direction,
+            };
+            let c = w.color_at(r);
+            assert!((c.red - 1.).abs() < 0.15, "{c:?}");
+        }
+    }
+
+    #[test]
+    fn replacing_the_environment_replaces_its_lighting() {
+        let uniform = |color: Color| {
+            let mut image = Canvas::new(8, 4);
+            for y in 0..4 {
+                for x in 0..8 {
+                    image.write_pixel(x, y, color);
+                }
+            }
+            EnvironmentLight::new(image, 64)
+        };
+        let mut w = World::new();
+        let mut ball = Sphere::default();
+        ball.material.ambient = 0.;
+        ball.material.specular = 0.;
+        w.add_object(ball);
+        let r = Ray {
+            origin: point!(0., 5., 0.),
+            direction: vector!(0., -1., 0.),
+        };
+
+        w.set_environment(uniform(Color::new(1., 1., 1.)));
+        let once = w.color_at(r);
+        w.set_environment(uniform(Color::new(1., 1., 1.)));
+        assert_eq!(w.color_at(r), once);
+        w.set_environment(uniform(Color::new(0., 0., 0.)));
+        assert_eq!(w.color_at(r), Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn color_when_ray_hits() {
+        let w = World::default();
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        test_color!(w.color_at(r), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn color_with_intersection_behind_ray() {
+        let mut w = World::default();
+        w.object_mut(0).material_mut().ambient = 1.;
+        w.object_mut(1).material_mut().ambient = 1.;
+        let inner = w.objects()[1].material().color;
+        let r = Ray {
+            origin: point!(0., 0., 0.75),
+            direction: vector!(0., 0., -1.),
+        };
+        assert_eq!(w.color_at(r), inner);
+    }
+
+    #[test]
+    fn no_shadow_when_nothing_is_collinear_with_point_and_light() {
+        let w = World::default();
+        let p = point!(0., 10., 0.);
+        assert!(!w.is_shadowed(p, &w.lights[0].samples(p)[0]));
+    }
+
+    #[test]
+    fn shadow_when_object_is_between_point_and_light() {
+        let w = World::default();
+        let p = point!(10., -10., 10.);
+        assert!(w.is_shadowed(p, &w.lights[0].samples(p)[0]));
+    }
+
+    #[test]
+    fn no_shadow_when_object_is_behind_the_light() {
+        let w = World::default();
+        let p = point!(-20., 20., -20.);
+        assert!(!w.is_shadowed(p, &w.lights[0].samples(p)[0]));
+    }
+
+    #[test]
+    fn no_shadow_when_object_is_behind_the_point() {
+        let w = World::default();
+        let p = point!(-2., 2., -2.);
+        assert!(!w.is_shadowed(p, &w.lights[0].samples(p)[0]));
+    }
+
+    #[test]
+    fn is_shadowed_tests_for_occlusion_between_two_points() {
+        let w = World::default();
+        let light_position = point!(-10., -10., -10.);
+        let cases = [
+            (point!(-10., -10., 10.), false),
+            (point!(10., 10., 10.), true),
+            (point!(-20., -20., -20.), false),
+            (point!(-5., -5., -5.), false),
+        ];
+        for (p, shadowed) in cases {
+            assert_eq!(w.is_shadowed_from(p, light_position), shadowed);
+        }
+    }
+
+    #[test]
+    fn area_light_visibility_is_the_fraction_of_unblocked_samples() {
+        let w = World::default();
+        let mut light = AreaLight::rectangle(
+            point!(-0.5, -0.5, -5.),
+            vector!(1., 0., 0.),
+            2,
+            vector!(0., 1., 0.),
+            2,
+            Color::new(1., 1., 1.),
+        );
+        light.jitter = false;
+        let cases = [
+            (point!(0., 0., 2.), 0.),
+            (point!(1., -1., 2.), 0.25),
+            (point!(1.5, 0., 2.), 0.5),
+            (point!(1.25, 1.25, 3.), 0.75),
+            (point!(0., 0., -2.), 1.),
+        ];
+        for (p, expected) in cases {
+            let samples = w.light_samples(p, &light);
+            let visible = samples.iter().map(|(_, v)| v).sum::<f64>() / samples.len() as f64;
+            assert!(equal(visible, expected));
+        }
+    }
+
+    #[test]
+    fn area_lights_cast_soft_shadows() {
+        let mut w = World::new();
+        let mut floor = Plane::default();
+        floor.material.specular = 0.;
+        w.add_object(floor);
+        let mut blocker = Sphere::default();
+        blocker.set_transform(Matrix4::translate(0., 2., 0.));
+        w.add_object(blocker);
+        w.add_light(AreaLight::rectangle(
+            point!(-2., 5., -2.),
+            vector!(4., 0., 0.),
+            8,
+            vector!(0., 0., 4.),
+            8,
+            Color::new(1., 1., 1.),
+        ));
+
+        let shade = |x: f64| {
+            let r = Ray {
+                origin: point!(x, 1., -0.1),
+                direction: vector!(0., -1., 0.),
+            };
+            w.color_at(r).red
+        };
+        let umbra = shade(0.);
+        let penumbra = shade(1.2);
+        let lit = shade(4.);
+        assert!(
+            umbra < penumbra && penumbra < lit,
+            "{umbra} {penumbra} {lit}"
+        );
+    }
+
+    #[test]
+    fn directional_light_shadows_reach_any_distance() {
+        let mut w = World::new();
+        let mut blocker = Sphere::default();
+        blocker.set_transform(Matrix4::translate(0., 1000., 0.));
+        w.add_object(blocker);
+        w.add_light(DirectionalLight::new(
+            vector!(0., -1., 0.),
+            Color::new(1., 1., 1.),
+        ));
+        let sample = w.lights[0].samples(point!(0., 0., 0.))[0];
+        assert!(w.is_shadowed(point!(0., 0., 0.), &sample));
+        assert!(!w.is_shadowed(point!(5., 0., 0.), &sample));
+    }
+
+    #[test]
+    fn spot_light_only_lights_inside_its_cone() {
+        let mut w = World::new();
+        let mut floor = Plane::default();
+        floor.material.ambient = 0.;
+        floor.material.specular = 0.;
+        w.add_object(floor);
+        w.add_light(SpotLight::new(
+            point!(0., 5., 0.),
+            point!(0., 0., 0.),
+            PI / 8.,
+            PI / 6.,
+            Color::new(1., 1., 1.),
+        ));
+        let shade = |x: f64| {
+            w.color_at(Ray {
+                origin: point!(x, 1., 0.),
+                direction: vector!(0., -1., 0.),
+            })
+        };
+        assert!(shade(0.).red > 0.8);
+        assert_eq!(shade(5.), Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn shade_hit_is_given_an_intersection_in_shadow() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(
+            point!(0., 0., -10.),
+            Color::new(1., 1., 1.),
+        ));
+        w.add_object(Sphere::default());
+        let mut s2 = Sphere::default();
+        s2.set_transform(Matrix4::translate(0., 0., 10.));
+        w.add_object(s2);
+        let r = Ray {
+            origin: point!(0., 0., 5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let i = Intersection::new(4., w.objects()[1].as_ref());
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        test_color!(
+            w.shade_hit(&comps, DEFAULT_MAX_DEPTH),
+            Color::new(0.1, 0.1, 0.1)
+        );
+    }
+
+    #[test]
+    fn world_rays_only_test_nearby_objects() {
+        let mut w = World::new();
+        w.record_bvh_stats(true);
+        for x in 0..20 {
+            for y in 0..20 {
+                let mut s = Sphere::default();
+                s.set_transform(Matrix4::translate(x as f64 * 3., y as f64 * 3., 0.));
+                w.add_object(s);
+            }
+        }
+        let r = Ray {
+            origin: point!(30., 30., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let xs = w.intersect_world(r);
+        assert_eq!(xs.0.len(), 2);
+        assert_eq!(xs.0[0].t, 4.);
+
+        let stats = w.bvh_stats();
+        assert_eq!(stats.rays, 1);
+        assert!(stats.shapes_tested * 20 < w.objects().len());
+    }
+
+    #[test]
+    fn moving_an_object_rebuilds_the_hierarchy() {
+        let mut w = World::default();
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        assert_eq!(w.intersect_world(r).0.len(), 4);
+        w.object_mut(0)
+            .set_transform(Matrix4::translate(0., 10., 0.));
+        assert_eq!(w.intersect_world(r).0.len(), 2);
+    }
+
+    /// The default world plus a half-reflective plane one unit below the origin.
+    fn world_with_reflective_floor() -> World {
+        let mut w = World::default();
+        let mut shape = Plane::default();
+        shape.material.reflective = 0.5;
+        shape.set_transform(Matrix4::translate(0., -1., 0.));
+        w.add_object(shape);
+        w
+    }
+
+    fn ray_at_floor() -> Ray {
+        Ray {
+            origin: point!(0., 0., -3.),
+            direction: vector!(0., -FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        }
+    }
+
+    #[test]
+    fn reflected_color_for_nonreflective_material() {
+        let mut w = World::default();
+        let r = Ray {
+            origin: point!(0., 0., 0.),
+            direction: vector!(0., 0., 1.),
+        };
+        w.object_mut(1).material_mut().ambient = 1.;
+        let i = Intersection::new(1., w.objects()[1].as_ref());
+        let comps = i.prepare_computations(r, &Intersections(vec![i]));
+        assert_eq!(
+            w.reflected_color(&comps, DEFAULT_MAX_DEPTH),
+            Color::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn reflected_color_for_reflective_material() {
+        let w = world_with_reflective_floor();
+        let i = Intersection::new(SQRT_2, w.objects()[2].as_ref());
+        let comps = i.prepare_computations(ray_at_floor(), &Intersections(vec![i]));
+        test_color!(
+            w.reflected_color(&comps, DEFAULT_MAX_DEPTH),
+            Color::new(0.19032, 0.2379, 0.14274)
+        );
+    }
+
+    #[test]
+    fn shade_hit_with_reflective_material() {
+        let w = world_with_reflective_floor();
+        let i = Intersection::new(SQRT_2, w.objects()[2].as_ref());
+        let comps = i.prepare_computations(ray_at_floor(), &Intersections(vec![i]));
+        test_color!(
+            w.shade_hit(&comps, DEFAULT_MAX_DEPTH),
+            Color::new(0.87677, 0.92436, 0.82918)
+        );
+    }
+
+    #[test]
+    fn color_at_with_mutually_reflective_surfaces() {
+        let mut w = World::new();
+        w.add_light(PointLight::new(point!(0., 0., 0.), Color::new(1., 1., 1.)));
+        let mut lower = Plane::default();
+        lower.material.reflective = 1.;
+        lower.set_transform(Matrix4::translate(0., -1., 0.));
+        w.add_object(lower);
+        let mut upper = Plane::default();
+        upper.material.reflective = 1.;
+        upper.set_transform(Matrix4::translate(0., 1., 0.));
+        w.add_object(upper);
+        let r = Ray {
+            origin: point!(0., 0., 0.),
+            direction: vector!(0., 1., 0.),
+        };
+        // terminating at all is the point of the test
+        w.color_at(r);
+    }
+
+    #[test]
+    fn reflected_color_at_maximum_recursive_depth() {
+        let w = world_with_reflective_floor();
+        let i = Intersection::new(SQRT_2, w.objects()[2].as_ref());
+        let comps = i.prepare_computations(ray_at_floor(), &Intersections(vec![i]));
+        assert_eq!(w.reflected_color(&comps, 0), Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn refracted_color_with_opaque_surface() {
+        let w = World::default();
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let shape = w.objects()[0].as_ref();
+        let xs = Intersections(vec![
+            Intersection::new(4., shape),
+            Intersection::new(6., shape),
+        ]);
+        let comps = xs.0[0].prepare_computations(r, &xs);
+        assert_eq!(
+            w.refracted_color(&comps, DEFAULT_MAX_DEPTH),
+            Color::new(0., 0., 0.)
+        );
+    }
+
+    #[test]
+    fn refracted_color_at_maximum_recursive_depth() {
+        let mut w = World::default();
+        *w.object_mut(0).material_mut() = Material::glass();
+        let r = Ray {
+            origin: point!(0., 0., -5.),
+            direction: vector!(0., 0., 1.),
+        };
+        let shape = w.objects()[0].as_ref();
+        let xs = Intersections(vec![
+            Intersection::new(4., shape),
+            Intersection::new(6., shape),
+        ]);
+        let comps = xs.0[0].prepare_computations(r, &xs);
+        assert_eq!(w.refracted_color(&comps, 0), Color::new(0., 0., 0.));
+    }
+
+    #[test]
+    fn refracted_color_under_total_internal_reflection() {
+        let mut w = World::default();
+        *w.object_mut(0).material_mut() = Material::glass();
+        let r = Ray {
+            origin: point!(0., 0., FRAC_1_SQRT_2),
+            direction: vector!(0., 1., 0.),
+        };
+        let shape = w.objects()[0].as_ref();
+        let xs = Intersections(vec![
+            Intersection::new(-FRAC_1_SQRT_2, shape),
+            Intersection::new(FRAC_1_SQRT_2, shape),
+        ]);
+        // inside the sphere, so look at the second intersection
+        let comps = xs.0[1].prepare_computations(r, &xs);
+        assert_eq!(
+            w.refracted_color(&comps, DEFAULT_MAX_DEPTH),
+            Color::new(0., 0., 0.)
+        );
+    }
+
+    /// The default world with a glass floor one unit down and a red ball beneath it.
+    fn world_with_glass_floor(reflective: f64) -> World {
+        let mut w = World::default();
+        let mut floor = Plane::default();
+        floor.set_transform(Matrix4::translate(0., -1., 0.));
+        floor.material.reflective = reflective;
+        floor.material.transparency = 0.5;
+        floor.material.refractive_index = 1.5;
+        w.add_object(floor);
+        let mut ball = Sphere::default();
+        ball.material.color = Color::new(1., 0., 0.);
+        ball.material.ambient = 0.5;
+        ball.set_transform(Matrix4::translate(0., -3.5, -0.5));
+        w.add_object(ball);
+        w
+    }
+
+    #[test]
+    fn shade_hit_with_transparent_material() {
+        let w = world_with_glass_floor(0.);
+        let xs = Intersections(vec![Intersection::new(SQRT_2, w.objects()[2].as_ref())]);
+        let comps = xs.0[0].prepare_computations(ray_at_floor(), &xs);
+        test_color!(
+            w.shade_hit(&comps, DEFAULT_MAX_DEPTH),
+            Color::new(0.93642, 0.68642, 0.68642)
+        );
+    }
+
+    #[test]
+    fn shade_hit_with_reflective_transparent_material() {
+        let w = world_with_glass_floor(0.5);
+        let xs = Intersections(vec![Intersection::new(SQRT_2, w.objects()[2].as_ref())]);
+        let comps = xs.0[0].prepare_computations(ray_at_floor(), &xs);
+        test_color!(
+            w.shade_hit(&comps, DEFAULT_MAX_DEPTH),
+            Color::new(0.93391, 0.69643, 0.69243)
+        );
+    }
+}